@@ -1,39 +1,87 @@
+use super::localization::Localization;
+use super::settings::AppSettings;
 use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
+use leafwing_input_manager::axislike::AxisType;
 use leafwing_input_manager::user_input::InputKind;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Debug;
 
-#[derive(Actionlike, TypePath, Clone, Copy)]
+#[derive(
+    Actionlike,
+    TypePath,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+)]
 pub enum AppActions {
     Screenshot,
     CloseMenu,
     LevelEditor,
     Continue,
+    /// Developer console
+    Console,
+    /// Developer mode: show entity under cursor in inspector
+    Inspect,
+    LogWindow,
 }
 
 impl AppActions {
-    fn default_map() -> InputMap<Self> {
+    pub fn default_map() -> InputMap<Self> {
         InputMap::default()
             .insert(KeyCode::F12, Self::Screenshot)
             .insert(KeyCode::Escape, Self::CloseMenu)
+            .insert(GamepadButtonType::Start, Self::CloseMenu)
             .insert_chord([KeyCode::ControlLeft, KeyCode::E], Self::LevelEditor)
             .insert(KeyCode::Space, Self::Continue)
+            .insert(GamepadButtonType::South, Self::Continue)
+            .insert(KeyCode::Grave, Self::Console)
+            .insert(KeyCode::F3, Self::Inspect)
+            .insert(KeyCode::F2, Self::LogWindow)
             .build()
     }
 }
 
-#[derive(Actionlike, TypePath, Clone, Copy)]
+#[derive(
+    Actionlike,
+    TypePath,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+)]
 pub enum PlayerActions {
     Movement, // action_axis_xy
+    /// Gamepad only, mouse cursor is used otherwise
+    Aim, // action_axis_xy
     ToggleHelp,
     Restart,
     Fire,
     Pull,
     Kick,
+    Dash,
+    Grab,
+    /// Switch camera zoom level
+    Zoom,
 }
 
 impl PlayerActions {
-    fn default_map() -> InputMap<Self> {
+    pub fn default_map() -> InputMap<Self> {
         InputMap::default()
             .insert(VirtualDPad::wasd(), Self::Movement)
             .insert(KeyCode::I, Self::ToggleHelp)
@@ -44,11 +92,38 @@ impl PlayerActions {
             .insert(KeyCode::K, Self::Fire)
             .insert(KeyCode::L, Self::Pull)
             .insert(KeyCode::F, Self::Kick)
+            .insert(KeyCode::ShiftLeft, Self::Dash)
+            .insert(KeyCode::E, Self::Grab)
+            .insert(MouseButton::Middle, Self::Grab)
+            .insert(KeyCode::Z, Self::Zoom)
+            //
+            .insert(DualAxis::left_stick(), Self::Movement)
+            .insert(DualAxis::right_stick(), Self::Aim)
+            .insert(GamepadButtonType::Select, Self::ToggleHelp)
+            .insert(GamepadButtonType::RightTrigger2, Self::Fire)
+            .insert(GamepadButtonType::LeftTrigger2, Self::Pull)
+            .insert(GamepadButtonType::West, Self::Kick)
+            .insert(GamepadButtonType::RightTrigger, Self::Dash)
+            .insert(GamepadButtonType::LeftTrigger, Self::Grab)
+            .insert(GamepadButtonType::DPadUp, Self::Zoom)
             .build()
     }
 }
 
-#[derive(Actionlike, TypePath, Clone, Copy)]
+#[derive(
+    Actionlike,
+    TypePath,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+)]
 pub enum EditorActions {
     Movement, // action_axis_xy
     Tool,
@@ -70,7 +145,7 @@ pub enum EditorActions {
 }
 
 impl EditorActions {
-    fn default_map() -> InputMap<Self> {
+    pub fn default_map() -> InputMap<Self> {
         InputMap::default()
             .insert(VirtualDPad::wasd(), Self::Movement)
             .insert(MouseButton::Left, Self::Tool)
@@ -95,6 +170,39 @@ impl EditorActions {
     }
 }
 
+/// Needed for rebinding actions in settings
+pub trait ActionInfo: Actionlike + Copy + Debug {
+    /// Bound to [`VirtualDPad`] instead of buttons
+    fn is_dpad(self) -> bool {
+        false
+    }
+}
+
+impl ActionInfo for AppActions {}
+
+impl ActionInfo for PlayerActions {
+    fn is_dpad(self) -> bool {
+        matches!(self, Self::Movement | Self::Aim)
+    }
+}
+
+impl ActionInfo for EditorActions {
+    fn is_dpad(self) -> bool {
+        matches!(self, Self::Movement)
+    }
+}
+
+/// Add default bindings for actions which have none, i.e. were added after settings were saved
+pub fn fill_missing_bindings<A: Actionlike>(map: &mut InputMap<A>, defaults: &InputMap<A>) {
+    for action in A::variants() {
+        if map.get(action.clone()).is_empty() {
+            for input in defaults.get(action.clone()).iter() {
+                map.insert(input.clone(), action.clone());
+            }
+        }
+    }
+}
+
 /// Normalized axis or zero
 pub fn action_axis_xy<T: Actionlike>(state: &ActionState<T>, action: T) -> Vec2 {
     state
@@ -108,6 +216,7 @@ pub fn action_axis_xy<T: Actionlike>(state: &ActionState<T>, action: T) -> Vec2
 #[derive(SystemParam)]
 pub struct ActionPrompt<'w, A: Actionlike + 'static> {
     map: Res<'w, InputMap<A>>,
+    localization: Res<'w, Localization>,
 }
 
 impl<'w, A: Actionlike + 'static> ActionPrompt<'w, A> {
@@ -115,33 +224,81 @@ impl<'w, A: Actionlike + 'static> ActionPrompt<'w, A> {
         let mut text = String::new();
 
         for input in self.map.get(action).iter() {
-            let s = match input {
-                UserInput::Single(input) => match input {
-                    InputKind::Keyboard(input) => format!("[{input:?} key]"),
-                    InputKind::Mouse(input) => format!("[{input:?} mouse button]"),
-                    _ => format!("[Single: {input:?}]"),
-                },
-                UserInput::Chord(input) => format!("[Chord: {input:?}]"),
-                UserInput::VirtualDPad(input) => {
-                    if input == &VirtualDPad::wasd() {
-                        "[W/A/S/D]".to_string()
-                    } else {
-                        format!("[VirtualDPad: {input:?}]")
-                    }
-                }
-                UserInput::VirtualAxis(input) => format!("[VirtualAxis: {input:?}]"),
-            };
-
             if !text.is_empty() {
-                text += " or ";
+                text += &self.localization.get("input.or");
             }
-            text += &s;
+            text += &input_name(input, &self.localization);
         }
 
         text
     }
 }
 
+/// Human-readable description of the input
+pub fn input_name(input: &UserInput, loc: &Localization) -> String {
+    match input {
+        UserInput::Single(input) => match input {
+            InputKind::Keyboard(key) => loc.format("input.key", &[("key", &format!("{key:?}"))]),
+            InputKind::Mouse(button) => {
+                loc.format("input.mouse", &[("button", &format!("{button:?}"))])
+            }
+            InputKind::GamepadButton(button) => loc.format(
+                "input.gamepad.button",
+                &[("button", &gamepad_button_name(*button, loc))],
+            ),
+            InputKind::DualAxis(input) => match (input.x.axis_type, input.y.axis_type) {
+                (
+                    AxisType::Gamepad(GamepadAxisType::LeftStickX),
+                    AxisType::Gamepad(GamepadAxisType::LeftStickY),
+                ) => format!("[{}]", loc.get("input.gamepad.left_stick")),
+                (
+                    AxisType::Gamepad(GamepadAxisType::RightStickX),
+                    AxisType::Gamepad(GamepadAxisType::RightStickY),
+                ) => format!("[{}]", loc.get("input.gamepad.right_stick")),
+                _ => format!("[DualAxis: {input:?}]"),
+            },
+            _ => format!("[Single: {input:?}]"),
+        },
+        UserInput::Chord(input) => format!("[Chord: {input:?}]"),
+        UserInput::VirtualDPad(input) => {
+            let keys = [input.up, input.left, input.down, input.right].map(|key| match key {
+                InputKind::Keyboard(key) => Some(format!("{key:?}")),
+                _ => None,
+            });
+            match keys {
+                [Some(up), Some(left), Some(down), Some(right)] => {
+                    format!("[{up}/{left}/{down}/{right}]")
+                }
+                _ => format!("[VirtualDPad: {input:?}]"),
+            }
+        }
+        UserInput::VirtualAxis(input) => format!("[VirtualAxis: {input:?}]"),
+    }
+}
+
+/// Xbox-style names
+fn gamepad_button_name(button: GamepadButtonType, loc: &Localization) -> String {
+    match button {
+        GamepadButtonType::South => "A".to_string(),
+        GamepadButtonType::East => "B".to_string(),
+        GamepadButtonType::West => "X".to_string(),
+        GamepadButtonType::North => "Y".to_string(),
+        GamepadButtonType::LeftTrigger => "LB".to_string(),
+        GamepadButtonType::RightTrigger => "RB".to_string(),
+        GamepadButtonType::LeftTrigger2 => "LT".to_string(),
+        GamepadButtonType::RightTrigger2 => "RT".to_string(),
+        GamepadButtonType::Select => "Back".to_string(),
+        GamepadButtonType::Start => "Start".to_string(),
+        GamepadButtonType::LeftThumb => loc.get("input.gamepad.left_stick"),
+        GamepadButtonType::RightThumb => loc.get("input.gamepad.right_stick"),
+        GamepadButtonType::DPadUp => loc.get("input.gamepad.dpad_up"),
+        GamepadButtonType::DPadDown => loc.get("input.gamepad.dpad_down"),
+        GamepadButtonType::DPadLeft => loc.get("input.gamepad.dpad_left"),
+        GamepadButtonType::DPadRight => loc.get("input.gamepad.dpad_right"),
+        other => format!("{other:?}"),
+    }
+}
+
 pub struct ActionsPlugin;
 
 impl Plugin for ActionsPlugin {
@@ -156,6 +313,21 @@ impl Plugin for ActionsPlugin {
         .init_resource::<ActionState<EditorActions>>()
         .insert_resource(AppActions::default_map())
         .insert_resource(PlayerActions::default_map())
-        .insert_resource(EditorActions::default_map());
+        .insert_resource(EditorActions::default_map())
+        .add_systems(
+            Update,
+            apply_controls.run_if(resource_changed::<AppSettings>()),
+        );
     }
 }
+
+fn apply_controls(
+    settings: Res<AppSettings>,
+    mut app: ResMut<InputMap<AppActions>>,
+    mut player: ResMut<InputMap<PlayerActions>>,
+    mut editor: ResMut<InputMap<EditorActions>>,
+) {
+    *app = settings.controls.app.clone();
+    *player = settings.controls.player.clone();
+    *editor = settings.controls.editor.clone();
+}
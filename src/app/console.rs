@@ -0,0 +1,218 @@
+use bevy::ecs::event::ManualEventReader;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Command typed in developer console.
+///
+/// Handled by plugins which registered it with [`ConsoleAppExt::add_console_command`].
+#[derive(Event, Clone, Debug)]
+pub struct ConsoleCommand {
+    pub name: String,
+    pub args: Vec<String>,
+
+    /// World position under mouse cursor when command was entered
+    pub cursor: Option<Vec2>,
+}
+
+impl ConsoleCommand {
+    /// Split input line into command name and arguments
+    pub fn parse(line: &str, cursor: Option<Vec2>) -> Option<Self> {
+        let mut words = line.split_whitespace().map(|word| word.to_string());
+        Some(Self {
+            name: words.next()?,
+            args: words.collect(),
+            cursor,
+        })
+    }
+
+    pub fn is(&self, name: &str) -> bool {
+        self.name == name
+    }
+
+    /// Parse argument, or return error text suitable for [`ConsoleOutput`]
+    pub fn arg<T: FromStr>(&self, index: usize) -> Result<T, String> {
+        let arg = self
+            .args
+            .get(index)
+            .ok_or_else(|| format!("{}: missing argument {}", self.name, index + 1))?;
+        arg.parse()
+            .map_err(|_| format!("{}: invalid argument \"{arg}\"", self.name))
+    }
+
+    /// Parse argument if it's present
+    pub fn arg_opt<T: FromStr>(&self, index: usize) -> Result<Option<T>, String> {
+        match self.args.get(index) {
+            Some(_) => self.arg(index).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Line printed in developer console
+#[derive(Event, Clone, Debug)]
+pub struct ConsoleOutput {
+    pub text: String,
+    pub error: bool,
+}
+
+impl ConsoleOutput {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            error: false,
+        }
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            error: true,
+        }
+    }
+
+    /// Print error if result is one
+    pub fn result(result: Result<String, String>) -> Self {
+        match result {
+            Ok(text) => Self::info(text),
+            Err(text) => Self::error(text),
+        }
+    }
+}
+
+/// Description of a registered command
+pub struct ConsoleCommandInfo {
+    /// Arguments, as shown to the user
+    pub usage: &'static str,
+    pub help: &'static str,
+}
+
+/// All registered commands, by name
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(pub BTreeMap<&'static str, ConsoleCommandInfo>);
+
+impl ConsoleCommands {
+    /// Names of commands starting with that text
+    pub fn complete<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'static str> + 'a {
+        self.0
+            .keys()
+            .copied()
+            .filter(move |name| name.starts_with(prefix))
+    }
+}
+
+pub trait ConsoleAppExt {
+    /// Command will be sent as [`ConsoleCommand`] event
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        help: &'static str,
+    ) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        help: &'static str,
+    ) -> &mut Self {
+        let mut commands = self.world.get_resource_or_insert_with(ConsoleCommands::default);
+        if commands
+            .0
+            .insert(name, ConsoleCommandInfo { usage, help })
+            .is_some()
+        {
+            warn!("console command \"{name}\" registered twice");
+        }
+        self
+    }
+}
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleCommands>()
+            .add_event::<ConsoleCommand>()
+            .add_event::<ConsoleOutput>()
+            .add_console_command("help", "[COMMAND]", "List commands or describe one")
+            .add_console_command("dump", "ENTITY", "List components of the entity (by index)")
+            .add_systems(
+                Update,
+                (
+                    help_command.run_if(on_event::<ConsoleCommand>()),
+                    dump_command.run_if(on_event::<ConsoleCommand>()),
+                ),
+            );
+    }
+}
+
+fn help_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    registry: Res<ConsoleCommands>,
+) {
+    for command in commands.read().filter(|command| command.is("help")) {
+        match command.args.first() {
+            Some(name) => match registry.0.get(name.as_str()) {
+                Some(info) => {
+                    output.send(ConsoleOutput::info(format!("{name} {}", info.usage)));
+                    output.send(ConsoleOutput::info(format!("  {}", info.help)));
+                }
+                None => output.send(ConsoleOutput::error(format!("no such command \"{name}\""))),
+            },
+            None => {
+                for (name, info) in registry.0.iter() {
+                    output.send(ConsoleOutput::info(format!(
+                        "{name} {} - {}",
+                        info.usage, info.help
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/// Component names are only available with full world access
+fn dump_command(world: &mut World, mut reader: Local<ManualEventReader<ConsoleCommand>>) {
+    let commands: Vec<_> = reader
+        .read(world.resource::<Events<ConsoleCommand>>())
+        .filter(|command| command.is("dump"))
+        .cloned()
+        .collect();
+
+    for command in commands {
+        let lines = command.arg::<u32>(0).and_then(|index| {
+            let entity = world
+                .entities()
+                .resolve_from_id(index)
+                .filter(|entity| world.get_entity(*entity).is_some())
+                .ok_or_else(|| format!("no entity with index {index}"))?;
+
+            let name = world
+                .get::<Name>(entity)
+                .map(|name| name.as_str().to_string())
+                .unwrap_or_default();
+            let mut lines = vec![format!("{entity:?} \"{name}\"")];
+            lines.extend(
+                world
+                    .inspect_entity(entity)
+                    .iter()
+                    .map(|info| format!("  {}", info.name())),
+            );
+            Ok(lines)
+        });
+
+        let mut output = world.resource_mut::<Events<ConsoleOutput>>();
+        match lines {
+            Ok(lines) => {
+                for line in lines {
+                    output.send(ConsoleOutput::info(line));
+                }
+            }
+            Err(error) => output.send(ConsoleOutput::error(error)),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::settings::AppSettings;
+use crate::gameplay::master::game_states::GameRunning;
+use crate::gameplay::master::level::current::CurrentLevel;
+use crate::gameplay::master::time_master::TimeMaster;
+use crate::utils::plugins::log_plugin::recent_log_lines;
+use bevy::prelude::*;
+use std::sync::Mutex;
+
+/// How many last log lines are included in the report
+const REPORT_LOG_LINES: usize = 100;
+
+/// Description of the app state, updated on changes.
+///
+/// Stored as text, because panic hook has no access to the world.
+static APP_STATE: Mutex<String> = Mutex::new(String::new());
+
+/// App version, state, settings and last log lines - for bug reports
+pub fn diagnostics_report() -> String {
+    let state = match APP_STATE.try_lock() {
+        Ok(state) => state.clone(),
+        Err(_) => "<state is not available>".to_string(),
+    };
+
+    let lines = recent_log_lines();
+    let lines = &lines[lines.len().saturating_sub(REPORT_LOG_LINES)..];
+
+    format!(
+        "{} {}\n\n== State ==\n{state}\n\n== Log ==\n{}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        lines.join("\n")
+    )
+}
+
+/// Write text to the file in userdata directory; returns file path on success
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_report(name: &str, text: &str) -> Option<String> {
+    use crate::utils::plugins::userdata_plugin::USER_DIRECTORY;
+
+    let path = format!("{USER_DIRECTORY}/{name}.txt");
+    let _ = std::fs::create_dir_all(USER_DIRECTORY);
+    match std::fs::write(&path, text) {
+        Ok(_) => Some(path),
+        Err(e) => {
+            error!("failed to write \"{path}\": {e}");
+            None
+        }
+    }
+}
+
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(not(target_arch = "wasm32"))]
+        set_panic_hook();
+
+        app.add_systems(
+            Last,
+            update_app_state.run_if(
+                resource_changed::<AppSettings>()
+                    .or_else(resource_changed::<CurrentLevel>())
+                    .or_else(resource_changed::<TimeMaster>())
+                    .or_else(state_changed::<GameRunning>()),
+            ),
+        );
+    }
+}
+
+fn update_app_state(
+    settings: Res<AppSettings>,
+    level: Res<CurrentLevel>,
+    time: Res<TimeMaster>,
+    game_running: Res<State<GameRunning>>,
+) {
+    let settings = ron::ser::to_string_pretty(&*settings, default())
+        .unwrap_or_else(|e| format!("<ron error: {e}>"));
+
+    let state = format!(
+        "level: \"{}\"\ngame running: {:?}\nin menu: {}, in editor: {}, speed: {}, scale: {:.2} {:?}\n\n== Settings ==\n{settings}",
+        level.id,
+        game_running.get(),
+        time.in_menu,
+        time.in_editor,
+        time.speed,
+        time.scale(),
+        time.modifiers().collect::<Vec<_>>(),
+    );
+
+    if let Ok(mut app_state) = APP_STATE.lock() {
+        *app_state = state;
+    }
+}
+
+/// Writes crash report to userdata directory, then calls the default hook
+#[cfg(not(target_arch = "wasm32"))]
+fn set_panic_hook() {
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let report = format!(
+            "{info}\n\n{}\n\n{}",
+            std::backtrace::Backtrace::force_capture(),
+            diagnostics_report()
+        );
+
+        if let Some(path) = write_report(&format!("crash_{timestamp}"), &report) {
+            eprintln!("crash report saved to \"{path}\"");
+        }
+
+        default_hook(info);
+    }));
+}
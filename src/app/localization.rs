@@ -0,0 +1,231 @@
+use super::console::ConsoleAppExt;
+use super::console::ConsoleCommand;
+use super::console::ConsoleOutput;
+use super::settings::AppSettings;
+use crate::gameplay::master::level::data::CustomAssetLoaderError;
+use crate::utils::plugins::load_assets::TrackAssets;
+use bevy::asset::AssetLoader;
+use bevy::asset::AsyncReadExt as _;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+/// Used when language isn't set, and for strings missing in the current language
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Codes of all languages. Tables are loaded from `lang/{code}.lang.ron`.
+pub const LANGUAGES: [&str; 2] = ["en", "ru"];
+
+/// Key of the language name, as written in that language
+const LANGUAGE_NAME_KEY: &str = "language.name";
+
+/// Localized strings by key
+#[derive(Asset, TypePath, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct StringTable(pub BTreeMap<String, String>);
+
+/// All UI and game text shown to the player should be taken from here.
+/// Developer tools (console, log, inspector, level editor) aren't localized.
+///
+/// Tables are available only after assets are loaded; until then keys are returned.
+#[derive(Resource, Default)]
+pub struct Localization {
+    /// Code of current language
+    language: String,
+    tables: HashMap<String, StringTable>,
+
+    /// Keys which were requested, but aren't present in the current language
+    missing: Mutex<BTreeSet<String>>,
+}
+
+impl Localization {
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Name of the language, in that language
+    pub fn language_name(&self, code: &str) -> String {
+        self.tables
+            .get(code)
+            .and_then(|table| table.0.get(LANGUAGE_NAME_KEY))
+            .cloned()
+            .unwrap_or_else(|| code.to_string())
+    }
+
+    /// Text in the current language; falls back to [`DEFAULT_LANGUAGE`] and then to the key itself
+    pub fn get(&self, key: &str) -> String {
+        if let Some(table) = self.tables.get(&self.language) {
+            if let Some(text) = table.0.get(key) {
+                return text.clone();
+            }
+            if let Ok(mut missing) = self.missing.lock() {
+                missing.insert(key.to_string());
+            }
+        }
+
+        self.tables
+            .get(DEFAULT_LANGUAGE)
+            .and_then(|table| table.0.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Same as [`Self::get`], with each `{name}` replaced by the value of that argument
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key);
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+
+    /// Problems with the current language table, one per line
+    pub fn missing_report(&self) -> Vec<String> {
+        let empty = StringTable::default();
+        let current = self.tables.get(&self.language).unwrap_or(&empty);
+        let default = self.tables.get(DEFAULT_LANGUAGE).unwrap_or(&empty);
+
+        let mut lines = vec![];
+        for key in default.0.keys().filter(|key| !current.0.contains_key(*key)) {
+            lines.push(format!("not translated: {key}"));
+        }
+        for key in current.0.keys().filter(|key| !default.0.contains_key(*key)) {
+            lines.push(format!("not in \"{DEFAULT_LANGUAGE}\": {key}"));
+        }
+        if let Ok(missing) = self.missing.lock() {
+            for key in missing.iter().filter(|key| !default.0.contains_key(*key)) {
+                lines.push(format!("requested, but missing: {key}"));
+            }
+        }
+        lines
+    }
+
+    fn set_language(&mut self, code: &str) {
+        let code = match code {
+            "" => DEFAULT_LANGUAGE,
+            code if LANGUAGES.contains(&code) => code,
+            code => {
+                warn!("unknown language \"{code}\"");
+                DEFAULT_LANGUAGE
+            }
+        };
+        if self.language != code {
+            self.language = code.to_string();
+            if let Ok(mut missing) = self.missing.lock() {
+                missing.clear();
+            }
+        }
+    }
+}
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<StringTable>()
+            .init_asset_loader::<StringTableLoader>()
+            .init_resource::<Localization>()
+            .add_console_command(
+                "missing_keys",
+                "",
+                "List strings missing in the current language",
+            )
+            .add_systems(Startup, load_tables)
+            .add_systems(
+                PreUpdate,
+                (
+                    update_tables,
+                    apply_language.run_if(resource_changed::<AppSettings>()),
+                ),
+            )
+            .add_systems(
+                Update,
+                missing_keys_command.run_if(on_event::<ConsoleCommand>()),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct TableHandles(Vec<(&'static str, Handle<StringTable>)>);
+
+fn load_tables(mut track: TrackAssets, mut commands: Commands) {
+    commands.insert_resource(TableHandles(
+        LANGUAGES
+            .iter()
+            .map(|code| (*code, track.load_and_track(format!("lang/{code}.lang.ron"))))
+            .collect(),
+    ));
+}
+
+fn update_tables(
+    mut localization: ResMut<Localization>,
+    handles: Option<Res<TableHandles>>,
+    assets: Res<Assets<StringTable>>,
+    mut asset_events: EventReader<AssetEvent<StringTable>>,
+) {
+    let Some(handles) = handles else { return; };
+
+    for event in asset_events.read() {
+        for (code, handle) in handles.0.iter() {
+            if event.is_loaded_with_dependencies(handle) || event.is_modified(handle) {
+                if let Some(table) = assets.get(handle) {
+                    localization.tables.insert(code.to_string(), table.clone());
+                }
+            }
+        }
+    }
+}
+
+fn apply_language(settings: Res<AppSettings>, mut localization: ResMut<Localization>) {
+    if localization.language != settings.language || localization.language.is_empty() {
+        localization.set_language(&settings.language);
+    }
+}
+
+fn missing_keys_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    localization: Res<Localization>,
+) {
+    for _ in commands.read().filter(|command| command.is("missing_keys")) {
+        let lines = localization.missing_report();
+        output.send(ConsoleOutput::info(format!(
+            "language \"{}\": {} problems",
+            localization.language(),
+            lines.len()
+        )));
+        for line in lines {
+            output.send(ConsoleOutput::info(line));
+        }
+    }
+}
+
+#[derive(Default)]
+struct StringTableLoader;
+
+impl AssetLoader for StringTableLoader {
+    type Asset = StringTable;
+    type Settings = ();
+    type Error = CustomAssetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut bevy::asset::io::Reader,
+        _settings: &'a (),
+        _load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::utils::BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<Self::Asset>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lang.ron"]
+    }
+}
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
 pub mod actions;
+pub mod console;
+pub mod diagnostics;
+pub mod localization;
 pub mod scheduling;
 pub mod scores;
 pub mod settings;
@@ -15,6 +18,9 @@ impl Plugin for AppPlugin {
         app.add_plugins((
             settings::SettingsPlugin,
             actions::ActionsPlugin,
+            console::ConsolePlugin,
+            diagnostics::DiagnosticsPlugin,
+            localization::LocalizationPlugin,
             scheduling::SchedulingPlugin,
             scores::ScoresPlugin,
         ));
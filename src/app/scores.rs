@@ -1,10 +1,15 @@
+use super::settings::AppSettings;
+use crate::gameplay::balance::Difficulty;
 use crate::gameplay::master::level::current::CurrentLevel;
 use crate::gameplay::master::level::current::LevelLoaded;
 use crate::gameplay::master::level_progress::GotoNextLevel;
+use crate::gameplay::master::level_progress::LevelProgressState;
+use crate::gameplay::mechanics::abilities::AbilityUpgrades;
 use crate::utils::plugins::userdata_plugin::Userdata;
 use bevy::prelude::*;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
 /// Player highscores and other progression data
@@ -16,6 +21,15 @@ pub struct Scores {
 
     /// IDs of completed levels
     pub completed_levels: BTreeSet<String>,
+
+    /// Highest difficulty on which level was completed, by level ID
+    pub completed_on: BTreeMap<String, Difficulty>,
+
+    /// Indices of objectives ever completed (together with the level), by level ID
+    pub completed_objectives: BTreeMap<String, BTreeSet<usize>>,
+
+    /// Player ability upgrades
+    pub upgrades: AbilityUpgrades,
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -64,9 +78,28 @@ fn update_visited_levels(
     mut scores: ResMut<Scores>,
     current: Res<CurrentLevel>,
     mut next_level: EventReader<GotoNextLevel>,
+    settings: Res<AppSettings>,
+    progress: Res<LevelProgressState>,
 ) {
     if let Some(next) = next_level.read().last() {
-        scores.completed_levels.insert(current.id.clone());
+        // upgrade point is given only once per level
+        if scores.completed_levels.insert(current.id.clone()) {
+            scores.upgrades.points += 1;
+        }
+
+        let difficulty = settings.gameplay.difficulty;
+        let completed_on = scores
+            .completed_on
+            .entry(current.id.clone())
+            .or_insert(difficulty);
+        *completed_on = (*completed_on).max(difficulty);
+
+        scores
+            .completed_objectives
+            .entry(current.id.clone())
+            .or_default()
+            .extend(progress.completed());
+
         match &next.id {
             Some(_) => {}
             None => {
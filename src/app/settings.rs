@@ -1,8 +1,15 @@
+use super::actions::fill_missing_bindings;
+use super::actions::AppActions;
+use super::actions::EditorActions;
+use super::actions::PlayerActions;
+use crate::gameplay::balance::Difficulty;
+use crate::utils::plugins::log_plugin::LogFilter;
 use crate::utils::plugins::userdata_plugin::Userdata;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy::window::WindowMode;
 use bevy_egui::EguiSettings;
+use leafwing_input_manager::prelude::InputMap;
 use serde::Deserialize;
 use serde::Serialize;
 use std::time::Duration;
@@ -14,20 +21,25 @@ use std::time::Duration;
 #[derive(Resource, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct AppSettings {
+    /// Language code; empty for default
+    pub language: String,
+
     pub log: LogSettings,
     pub debug: DebugSettings,
     pub graphics: GraphicalSettings,
+    pub gameplay: GameplaySettings,
+    pub controls: ControlSettings,
 }
 
 /// In-app log display
 #[derive(Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct LogSettings {
-    /// Show all messages
-    pub show_all: bool,
-
-    /// Ignored if [`Self::show_all`] is `true`
+    /// Don't notify about new errors while log window is closed
     pub hide_errors: bool,
+
+    /// Directives added to the log filter, in `EnvFilter` format
+    pub filter: String,
 }
 
 /// Debug options
@@ -39,6 +51,9 @@ pub struct DebugSettings {
     pub show_fps: bool,
     pub show_physics: bool,
 
+    /// Frame times, entity counts and system timings
+    pub show_perf: bool,
+
     /// On startup go to play last played level
     pub quick_start: bool,
 
@@ -46,6 +61,51 @@ pub struct DebugSettings {
     pub quick_edit: bool,
 }
 
+/// Gameplay
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameplaySettings {
+    pub difficulty: Difficulty,
+    /// Open pause menu when game window isn't focused
+    pub pause_on_focus_loss: bool,
+}
+
+impl Default for GameplaySettings {
+    fn default() -> Self {
+        Self {
+            difficulty: default(),
+            pause_on_focus_loss: true,
+        }
+    }
+}
+
+/// Key bindings
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlSettings {
+    pub app: InputMap<AppActions>,
+    pub player: InputMap<PlayerActions>,
+    pub editor: InputMap<EditorActions>,
+}
+
+impl ControlSettings {
+    fn fill_missing(&mut self) {
+        fill_missing_bindings(&mut self.app, &AppActions::default_map());
+        fill_missing_bindings(&mut self.player, &PlayerActions::default_map());
+        fill_missing_bindings(&mut self.editor, &EditorActions::default_map());
+    }
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        Self {
+            app: AppActions::default_map(),
+            player: PlayerActions::default_map(),
+            editor: EditorActions::default_map(),
+        }
+    }
+}
+
 /// Graphics
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
@@ -58,6 +118,7 @@ pub struct GraphicalSettings {
 
     pub shadows: bool,
     pub starfield: bool,
+    pub screen_shake: bool,
 }
 
 impl Default for GraphicalSettings {
@@ -67,6 +128,7 @@ impl Default for GraphicalSettings {
             fullscreen: false,
             shadows: true,
             starfield: true,
+            screen_shake: true,
         }
     }
 }
@@ -98,6 +160,7 @@ impl Plugin for SettingsPlugin {
 
 fn load_settings(mut settings: ResMut<AppSettings>, userdata: Res<Userdata>) {
     *settings = userdata.read_and_update(USERDATA_NAME);
+    settings.controls.fill_missing();
 }
 
 #[derive(Resource)]
@@ -126,6 +189,7 @@ fn apply_settings(
     mut window: Query<&mut Window, With<PrimaryWindow>>,
     #[cfg(feature = "dev_build")] mut rapier: ResMut<bevy_rapier2d::render::DebugRenderContext>,
     mut point_lights: Query<&mut PointLight, With<LightWithShadows>>,
+    mut log_filter: ResMut<LogFilter>,
 ) {
     egui_settings.scale_factor = settings.graphics.ui_scale as f64;
 
@@ -144,4 +208,10 @@ fn apply_settings(
     for mut light in point_lights.iter_mut() {
         light.shadows_enabled = settings.graphics.shadows;
     }
+
+    if settings.log.filter != log_filter.extra() {
+        if let Err(e) = log_filter.set_extra(&settings.log.filter) {
+            error!("invalid log filter \"{}\": {e}", settings.log.filter);
+        }
+    }
 }
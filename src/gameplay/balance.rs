@@ -1,19 +1,335 @@
+use super::master::level::data::CustomAssetLoaderError;
 use super::master::level::data::TILE_SIZE;
+use super::mechanics::abilities::Ability;
+use super::mechanics::abilities::AbilityStats;
+use super::objects::enemy::Enemy;
+use super::objects::enemy::EnemyStats;
+use super::objects::particles::Particle;
+use super::objects::particles::ParticleDescriptor;
+use crate::app::localization::Localization;
+use crate::app::settings::AppSettings;
+use crate::utils::misc_utils::serde_duration_secs;
+use crate::utils::misc_utils::serde_sorted_map;
+use crate::utils::plugins::load_assets::TrackAssets;
+use bevy::asset::AssetLoader;
+use bevy::asset::AsyncReadExt as _;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use serde::Serialize;
 use std::time::Duration;
 
-pub const OVERLOAD_ENEMY_REGULAR: f32 = 5.;
-pub const OVERLOAD_RADIUS: f32 = TILE_SIZE * 5.;
+/// Path to the balance file, relative to assets directory
+const BALANCE_PATH: &str = "game.balance.ron";
 
-pub const OVERLOAD_PROJECTILE_IMPACT: f32 = 0.03;
-pub const OVERLOAD_FIRE_IMPACT: f32 = 0.05;
-pub const OVERLOAD_BURNING_BARREL: f32 = 0.25;
-pub const OVERLOAD_EXPLOSION: f32 = 1.;
-pub const OVERLOAD_OVERLOADED: f32 = 0.4;
+/// Selected in settings, scales [`Balance`]
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Serialize, Deserialize,
+)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
 
-pub const OVERLOAD_DURATION_PARTICLE: Duration = Duration::from_millis(1000);
-pub const OVERLOAD_DURATION_EXPLOSION: Duration = Duration::from_millis(5000);
-pub const OVERLOAD_DURATION_OVERLOADED: Duration = Duration::from_millis(3000);
+impl Difficulty {
+    pub fn all() -> impl Iterator<Item = Self> {
+        [Self::Easy, Self::Normal, Self::Hard].into_iter()
+    }
 
-pub const DURATION_FIREBALL: Duration = Duration::from_millis(600);
-pub const SPEED_FIREBALL_PLAYER: f32 = 5.;
-pub const SPEED_FIREBALL_EXPLOSION: f32 = 6.;
+    pub fn name(self, loc: &Localization) -> String {
+        loc.get(match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Normal => "difficulty.normal",
+            Difficulty::Hard => "difficulty.hard",
+        })
+    }
+
+    /// Multiplier for values which make game easier when increased
+    fn k(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 0.7,
+        }
+    }
+}
+
+/// Gameplay values, loaded from [`BALANCE_PATH`] asset (and reloaded when it changes in dev builds).
+///
+/// Resource is the loaded asset scaled by [`Difficulty`], it's updated automatically when
+/// settings or asset change. Some values stored in components are updated on live entities;
+/// the rest (i.e. health) are applied only when they're spawned.
+///
+/// Default is used until asset is loaded, and for the missing fields.
+#[derive(Resource, Asset, TypePath, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Balance {
+    pub player_health: u32,
+    pub player_energy: f32,
+    /// Per second
+    pub player_energy_regen: f32,
+    pub player_fireball_speed: f32,
+    #[serde(serialize_with = "serde_sorted_map")]
+    pub abilities: HashMap<Ability, AbilityStats>,
+
+    /// AI has delayed reaction to player appearing in their field of view,
+    /// depending on distance: from min to max.
+    #[serde(with = "serde_duration_secs")]
+    pub enemy_reaction_delay_min: Duration,
+    #[serde(with = "serde_duration_secs")]
+    pub enemy_reaction_delay_max: Duration,
+    #[serde(serialize_with = "serde_sorted_map")]
+    pub enemies: HashMap<Enemy, EnemyStats>,
+
+    /// Overload sources affect entities in that radius
+    pub overload_radius: f32,
+    pub overload_burning_barrel: f32,
+    pub overload_explosion: f32,
+    pub overload_overloaded: f32,
+    /// Per stack of burning status effect
+    pub overload_burning_status: f32,
+    #[serde(with = "serde_duration_secs")]
+    pub overload_duration_explosion: Duration,
+    #[serde(with = "serde_duration_secs")]
+    pub overload_duration_overloaded: Duration,
+
+    /// Lifetime of fireballs created by explosions
+    #[serde(with = "serde_duration_secs")]
+    pub explosion_fireball_duration: Duration,
+    pub explosion_fireball_speed: f32,
+    /// Shockwave radius: bodies outside of it are not affected
+    pub explosion_radius: f32,
+    /// Impulse at the center, decreases linearly to zero at the radius
+    pub explosion_impulse: f32,
+    /// At the center
+    pub explosion_damage_barrels: f32,
+    /// At the center
+    pub explosion_damage_player: f32,
+    /// Fraction of damage which is still applied at the radius
+    pub explosion_damage_falloff: f32,
+    /// Barrels in that radius are ignited by the explosion (if not blocked by walls)
+    pub explosion_ignition_radius: f32,
+    /// Delay before ignited barrel explodes: from closest to farthest
+    #[serde(with = "serde_duration_secs")]
+    pub explosion_ignition_delay_min: Duration,
+    #[serde(with = "serde_duration_secs")]
+    pub explosion_ignition_delay_max: Duration,
+
+    #[serde(with = "serde_duration_secs")]
+    pub status_burning_duration: Duration,
+    /// Per second per stack
+    pub status_burning_damage: f32,
+    #[serde(with = "serde_duration_secs")]
+    pub status_stunned_duration: Duration,
+    #[serde(with = "serde_duration_secs")]
+    pub status_slowed_duration: Duration,
+    /// Speed multiplier
+    pub status_slowed_speed: f32,
+
+    /// Slow-motion starts when that many explosions happen within the window; 0 disables it
+    pub slowmo_explosions: u32,
+    #[serde(with = "serde_duration_secs")]
+    pub slowmo_explosion_window: Duration,
+    /// Game speed multiplier
+    pub slowmo_scale: f32,
+    /// Real time, not including ramps
+    #[serde(with = "serde_duration_secs")]
+    pub slowmo_duration: Duration,
+    #[serde(with = "serde_duration_secs")]
+    pub slowmo_ramp: Duration,
+
+    #[serde(serialize_with = "serde_sorted_map")]
+    pub particles: HashMap<Particle, ParticleDescriptor>,
+}
+
+impl Default for Balance {
+    fn default() -> Self {
+        Self {
+            player_health: 30,
+            player_energy: 100.,
+            player_energy_regen: 25.,
+            player_fireball_speed: 5.,
+            abilities: Ability::all()
+                .map(|ability| (ability, ability.default_stats()))
+                .collect(),
+
+            enemy_reaction_delay_min: Duration::from_millis(1000),
+            enemy_reaction_delay_max: Duration::from_millis(2000),
+            enemies: Enemy::all()
+                .map(|enemy| (enemy, EnemyStats::default()))
+                .collect(),
+
+            overload_radius: TILE_SIZE * 5.,
+            overload_burning_barrel: 0.25,
+            overload_explosion: 1.,
+            overload_overloaded: 0.4,
+            overload_burning_status: 1.,
+            overload_duration_explosion: Duration::from_millis(5000),
+            overload_duration_overloaded: Duration::from_millis(3000),
+
+            explosion_fireball_duration: Duration::from_millis(600),
+            explosion_fireball_speed: 6.,
+            explosion_radius: 3.,
+            explosion_impulse: 500.,
+            explosion_damage_barrels: 3.,
+            explosion_damage_player: 2.,
+            explosion_damage_falloff: 0.5,
+            explosion_ignition_radius: TILE_SIZE * 2.5,
+            explosion_ignition_delay_min: Duration::from_millis(300),
+            explosion_ignition_delay_max: Duration::from_millis(1200),
+
+            status_burning_duration: Duration::from_millis(2000),
+            status_burning_damage: 1.,
+            status_stunned_duration: Duration::from_millis(1500),
+            status_slowed_duration: Duration::from_millis(1000),
+            status_slowed_speed: 0.6,
+
+            slowmo_explosions: 4,
+            slowmo_explosion_window: Duration::from_millis(600),
+            slowmo_scale: 0.3,
+            slowmo_duration: Duration::from_millis(700),
+            slowmo_ramp: Duration::from_millis(150),
+
+            particles: Particle::all()
+                .map(|particle| (particle, particle.default_descriptor()))
+                .collect(),
+        }
+    }
+}
+
+impl Balance {
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        let k = difficulty.k();
+
+        Self {
+            player_health: (self.player_health as f32 * k).round() as u32,
+            player_energy_regen: self.player_energy_regen * k.sqrt(),
+            abilities: self
+                .abilities
+                .into_iter()
+                .map(|(ability, stats)| {
+                    let stats = AbilityStats {
+                        energy_cost: stats.energy_cost / k.sqrt(),
+                        ..stats
+                    };
+                    (ability, stats)
+                })
+                .collect(),
+            enemy_reaction_delay_min: self.enemy_reaction_delay_min.mul_f32(k),
+            enemy_reaction_delay_max: self.enemy_reaction_delay_max.mul_f32(k),
+            enemies: self
+                .enemies
+                .into_iter()
+                .map(|(enemy, stats)| {
+                    let stats = EnemyStats {
+                        overload: stats.overload / k.sqrt(),
+                        shoot_period: stats.shoot_period.mul_f32(k),
+                        ..stats
+                    };
+                    (enemy, stats)
+                })
+                .collect(),
+            explosion_damage_player: self.explosion_damage_player / k,
+            ..self
+        }
+    }
+
+    pub fn ability(&self, ability: Ability) -> &AbilityStats {
+        &self.abilities[&ability]
+    }
+
+    pub fn enemy(&self, enemy: Enemy) -> &EnemyStats {
+        &self.enemies[&enemy]
+    }
+
+    pub fn particle(&self, particle: Particle) -> &ParticleDescriptor {
+        &self.particles[&particle]
+    }
+
+    /// Tables may be only partially specified in the file
+    fn fill_missing(&mut self) {
+        let default = Self::default();
+        for (ability, stats) in default.abilities {
+            self.abilities.entry(ability).or_insert(stats);
+        }
+        for (enemy, stats) in default.enemies {
+            self.enemies.entry(enemy).or_insert(stats);
+        }
+        for (particle, descriptor) in default.particles {
+            self.particles.entry(particle).or_insert(descriptor);
+        }
+    }
+}
+
+pub struct BalancePlugin;
+
+impl Plugin for BalancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Balance>()
+            .init_asset_loader::<BalanceLoader>()
+            .init_resource::<Balance>()
+            .add_systems(Startup, load_balance)
+            .add_systems(PreUpdate, update_balance);
+    }
+}
+
+#[derive(Resource)]
+struct BalanceHandle(Handle<Balance>);
+
+fn load_balance(mut track: TrackAssets, mut commands: Commands) {
+    commands.insert_resource(BalanceHandle(track.load_and_track(BALANCE_PATH)));
+}
+
+fn update_balance(
+    mut balance: ResMut<Balance>,
+    settings: Res<AppSettings>,
+    handle: Option<Res<BalanceHandle>>,
+    assets: Res<Assets<Balance>>,
+    mut asset_events: EventReader<AssetEvent<Balance>>,
+) {
+    let Some(handle) = handle else { return; };
+
+    let asset_changed = asset_events
+        .read()
+        .filter(|event| {
+            event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0)
+        })
+        .count()
+        != 0;
+    if !asset_changed && !settings.is_changed() {
+        return;
+    }
+
+    let base = assets.get(&handle.0).cloned().unwrap_or_default();
+    *balance = base.with_difficulty(settings.gameplay.difficulty);
+}
+
+#[derive(Default)]
+struct BalanceLoader;
+
+impl AssetLoader for BalanceLoader {
+    type Asset = Balance;
+    type Settings = ();
+    type Error = CustomAssetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut bevy::asset::io::Reader,
+        _settings: &'a (),
+        _load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::utils::BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let mut balance = ron::de::from_bytes::<Self::Asset>(&bytes)?;
+            balance.fill_missing();
+            Ok(balance)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["balance.ron"]
+    }
+}
@@ -0,0 +1,327 @@
+use super::game_states::GameCommand;
+use super::level::current::CurrentLevel;
+use super::level::current::LevelLoaded;
+use super::level::data::LevelObjectId;
+use super::level::data::TILE_SIZE;
+use super::level::spawn::SpawnedBy;
+use super::level_progress::LevelProgressState;
+use crate::app::scheduling::SpawnSet;
+use crate::gameplay::balance::Balance;
+use crate::gameplay::mechanics::damage::Dead;
+use crate::gameplay::mechanics::damage::Health;
+use crate::gameplay::mechanics::overload::Overload;
+use crate::gameplay::mechanics::MechanicSet;
+use crate::gameplay::objects::barrels::ignite;
+use crate::gameplay::objects::barrels::Barrel;
+use crate::gameplay::objects::barrels::OnFire;
+use crate::gameplay::objects::player::Player;
+use crate::utils::bevy::commands::FallibleCommands;
+use crate::utils::plugins::userdata_plugin::Userdata;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+/// Level object. When player gets close, state of the level is saved, and respawn restores it.
+#[derive(Component, Serialize, Deserialize, Clone, Default, Debug)]
+pub struct Checkpoint;
+
+/// Added to [`Checkpoint`] after it was reached
+#[derive(Component)]
+pub struct CheckpointReached;
+
+/// Dynamic gameplay state of the level at the last reached checkpoint.
+///
+/// Only objects spawned from level data are saved.
+#[derive(Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LevelSnapshot {
+    pub level_id: String,
+    pub reached: BTreeSet<LevelObjectId>,
+
+    pub player: Option<PlayerSnapshot>,
+    /// Barrels which are not here were destroyed
+    pub barrels: HashMap<LevelObjectId, BarrelSnapshot>,
+    /// By [`SpawnedBy`]
+    pub enemies: HashMap<LevelObjectId, EnemySnapshot>,
+    pub progress: LevelProgressState,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub pos: Vec2,
+    pub health: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BarrelSnapshot {
+    pub pos: Vec2,
+    /// If barrel is on fire, time left before it explodes
+    #[serde(default)]
+    pub explode_in: Option<Duration>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EnemySnapshot {
+    pub overload: f32,
+    pub dead: bool,
+}
+
+/// Last checkpoint, saved to userdata
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Checkpoints {
+    pub snapshot: Option<LevelSnapshot>,
+}
+
+impl Checkpoints {
+    /// Snapshot only if it's for that level
+    pub fn for_level(&self, level_id: &str) -> Option<&LevelSnapshot> {
+        self.snapshot
+            .as_ref()
+            .filter(|snapshot| snapshot.level_id == level_id)
+    }
+}
+
+/// Checkpoint is reached if player is that close to it
+const CHECKPOINT_RADIUS: f32 = TILE_SIZE;
+
+const USERDATA_NAME: &str = "checkpoint";
+
+pub struct CheckpointsPlugin;
+
+impl Plugin for CheckpointsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Checkpoints>()
+            .add_systems(PreStartup, load_checkpoints)
+            .add_systems(
+                FixedUpdate,
+                reach_checkpoints.after(MechanicSet::PostReaction),
+            )
+            .add_systems(
+                Update,
+                (
+                    on_game_command.run_if(on_event::<GameCommand>()),
+                    on_level_loaded.run_if(on_event::<LevelLoaded>()),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    (
+                        restore_checkpoints,
+                        restore_player,
+                        restore_barrels,
+                        restore_enemies,
+                    )
+                        .in_set(SpawnSet::Details),
+                    save_checkpoints.run_if(resource_changed::<Checkpoints>()),
+                ),
+            );
+    }
+}
+
+fn load_checkpoints(mut checkpoints: ResMut<Checkpoints>, userdata: Res<Userdata>) {
+    *checkpoints = userdata.read_and_update(USERDATA_NAME);
+}
+
+fn save_checkpoints(checkpoints: Res<Checkpoints>, userdata: Res<Userdata>) {
+    userdata.write(USERDATA_NAME, &*checkpoints);
+}
+
+/// Snapshot is discarded when level is started anew
+fn on_game_command(
+    mut game_commands: EventReader<GameCommand>,
+    mut checkpoints: ResMut<Checkpoints>,
+) {
+    for command in game_commands.read() {
+        // don't trigger saving if there is nothing to discard
+        if let GameCommand::Start { .. } = command {
+            if checkpoints.snapshot.is_some() {
+                checkpoints.snapshot = None;
+            }
+        }
+    }
+}
+
+/// Snapshot is discarded when another level is loaded
+fn on_level_loaded(
+    mut level_loaded: EventReader<LevelLoaded>,
+    mut checkpoints: ResMut<Checkpoints>,
+) {
+    let Some(loaded) = level_loaded.read().last() else { return; };
+
+    if checkpoints.snapshot.is_some() && checkpoints.for_level(&loaded.id).is_none() {
+        checkpoints.snapshot = None;
+    }
+}
+
+/// Checkpoints which player hasn't reached yet
+type NewCheckpoints<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static GlobalTransform, &'static LevelObjectId),
+    (With<Checkpoint>, Without<CheckpointReached>),
+>;
+
+/// Alive player
+type PlayerState<'w, 's> =
+    Query<'w, 's, (&'static GlobalTransform, &'static Health), (With<Player>, Without<Dead>)>;
+
+type SnapshotBarrels<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        &'static LevelObjectId,
+        Option<&'static OnFire>,
+    ),
+    (With<Barrel>, Without<Dead>),
+>;
+
+/// Everything saved in [`LevelSnapshot`], except the player
+#[derive(SystemParam)]
+struct SnapshotSource<'w, 's> {
+    reached: Query<'w, 's, &'static LevelObjectId, With<CheckpointReached>>,
+    barrels: SnapshotBarrels<'w, 's>,
+    enemies: Query<'w, 's, (&'static SpawnedBy, &'static Overload, Has<Dead>)>,
+    progress: Res<'w, LevelProgressState>,
+    current: Res<'w, CurrentLevel>,
+    /// Same clock as the fire in [`FixedUpdate`]
+    time: Res<'w, Time<Fixed>>,
+}
+
+impl<'w, 's> SnapshotSource<'w, 's> {
+    /// `checkpoint` is counted as reached
+    fn snapshot(&self, checkpoint: LevelObjectId, player: PlayerSnapshot) -> LevelSnapshot {
+        LevelSnapshot {
+            level_id: self.current.id.clone(),
+            reached: self.reached.iter().copied().chain([checkpoint]).collect(),
+            player: Some(player),
+            barrels: self
+                .barrels
+                .iter()
+                .map(|(pos, id, on_fire)| {
+                    let pos = pos.translation().truncate();
+                    let explode_in = on_fire
+                        .map(|on_fire| on_fire.explode_at.saturating_sub(self.time.elapsed()));
+                    (*id, BarrelSnapshot { pos, explode_in })
+                })
+                .collect(),
+            enemies: self
+                .enemies
+                .iter()
+                .map(|(spawned_by, overload, dead)| {
+                    let overload = overload.current;
+                    (spawned_by.0, EnemySnapshot { overload, dead })
+                })
+                .collect(),
+            progress: self.progress.snapshot(),
+        }
+    }
+}
+
+fn reach_checkpoints(
+    new: NewCheckpoints,
+    player: PlayerState,
+    source: SnapshotSource,
+    mut commands: Commands,
+    mut checkpoints: ResMut<Checkpoints>,
+) {
+    let Ok((player_pos, health)) = player.get_single() else { return; };
+    let player_pos = player_pos.translation().truncate();
+
+    for (entity, pos, id) in new.iter() {
+        if pos.translation().truncate().distance(player_pos) > CHECKPOINT_RADIUS {
+            continue;
+        }
+
+        info!("reached checkpoint {id:?}");
+        commands.try_insert(entity, CheckpointReached);
+
+        let player = PlayerSnapshot {
+            pos: player_pos,
+            health: health.value,
+        };
+        checkpoints.snapshot = Some(source.snapshot(*id, player));
+        break;
+    }
+}
+
+fn restore_checkpoints(
+    new: Query<(Entity, &LevelObjectId), Added<Checkpoint>>,
+    checkpoints: Res<Checkpoints>,
+    current: Res<CurrentLevel>,
+    mut commands: Commands,
+) {
+    let Some(snapshot) = checkpoints.for_level(&current.id) else { return; };
+
+    for (entity, id) in new.iter() {
+        if snapshot.reached.contains(id) {
+            commands.try_insert(entity, CheckpointReached);
+        }
+    }
+}
+
+/// Player is spawned exactly once per level (re)spawn, so progress is restored here too
+fn restore_player(
+    mut new: Query<(&mut Transform, &mut Health), Added<Player>>,
+    checkpoints: Res<Checkpoints>,
+    current: Res<CurrentLevel>,
+    mut progress: ResMut<LevelProgressState>,
+) {
+    let Some(snapshot) = checkpoints.for_level(&current.id) else { return; };
+
+    for (mut transform, mut health) in new.iter_mut() {
+        if let Some(player) = &snapshot.player {
+            transform.translation = player.pos.extend(transform.translation.z);
+            health.value = player.health;
+        }
+
+        *progress = snapshot.progress.clone();
+    }
+}
+
+fn restore_barrels(
+    mut new: Query<(Entity, &mut Transform, &LevelObjectId), Added<Barrel>>,
+    checkpoints: Res<Checkpoints>,
+    current: Res<CurrentLevel>,
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    balance: Res<Balance>,
+) {
+    let Some(snapshot) = checkpoints.for_level(&current.id) else { return; };
+
+    for (entity, mut transform, id) in new.iter_mut() {
+        match snapshot.barrels.get(id) {
+            Some(barrel) => {
+                transform.translation = barrel.pos.extend(transform.translation.z);
+                if let Some(explode_in) = barrel.explode_in {
+                    ignite(&mut commands, &balance, entity, time.elapsed(), explode_in);
+                }
+            }
+            None => commands.try_despawn_recursive(entity),
+        }
+    }
+}
+
+fn restore_enemies(
+    mut new: Query<(Entity, &SpawnedBy, &mut Overload), Added<Overload>>,
+    checkpoints: Res<Checkpoints>,
+    current: Res<CurrentLevel>,
+    mut commands: Commands,
+) {
+    let Some(snapshot) = checkpoints.for_level(&current.id) else { return; };
+
+    for (entity, spawned_by, mut overload) in new.iter_mut() {
+        if let Some(enemy) = snapshot.enemies.get(&spawned_by.0) {
+            overload.current = enemy.overload;
+            if enemy.dead {
+                commands.try_insert(entity, Dead);
+            }
+        }
+    }
+}
@@ -1,9 +1,13 @@
+use super::checkpoints::Checkpoints;
 use super::level::current::CurrentLevel;
 use super::level::current::LevelLoaded;
 use crate::app::actions::ActionPrompt;
 use crate::app::actions::PlayerActions;
+use crate::app::localization::Localization;
+use crate::presentation::CameraPan;
 use crate::presentation::DelayedMessage;
 use crate::presentation::Message;
+use crate::presentation::MessageCategory;
 use bevy::prelude::*;
 use std::time::Duration;
 
@@ -11,7 +15,10 @@ pub struct GameScriptPlugin;
 
 impl Plugin for GameScriptPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, on_level_loaded.run_if(on_event::<LevelLoaded>()));
+        app.add_systems(
+            Update,
+            (on_level_loaded, show_level_intro).run_if(on_event::<LevelLoaded>()),
+        );
     }
 }
 
@@ -21,6 +28,7 @@ fn on_level_loaded(
     prompt: ActionPrompt<PlayerActions>,
     mut current_level: ResMut<CurrentLevel>,
     mut commands: Commands,
+    loc: Res<Localization>,
 ) {
     let Some(loaded) = level_loaded.read().last() else { return; };
 
@@ -32,17 +40,19 @@ fn on_level_loaded(
     match loaded.id.as_str() {
         "01_cells" => {
             messages.send(
-                Message::notify("Tutorial", "Look into bottom left corner to see tutorial.")
+                Message::notify(loc.get("message.tutorial"), loc.get("script.tutorial_corner"))
+                    .with_category(MessageCategory::Tutorial)
                     .delay(Duration::from_millis(1200), true),
             );
             messages.send(
                 Message::notify(
-                    "Tutorial",
-                    format!(
-                        "Press {} to show objective and controls",
-                        prompt.get(PlayerActions::ToggleHelp)
+                    loc.get("message.tutorial"),
+                    loc.format(
+                        "script.help_hint",
+                        &[("key", &prompt.get(PlayerActions::ToggleHelp))],
                     ),
                 )
+                .with_category(MessageCategory::Tutorial)
                 .delay(Duration::from_millis(6000), true),
             );
         }
@@ -67,3 +77,23 @@ fn on_level_loaded(
 
     commands.insert_resource(ambient);
 }
+
+/// Camera pans are shown only on the first entry, not when continuing from a checkpoint
+fn show_level_intro(
+    mut level_loaded: EventReader<LevelLoaded>,
+    mut camera_pans: EventWriter<CameraPan>,
+    checkpoints: Res<Checkpoints>,
+) {
+    let Some(loaded) = level_loaded.read().last() else { return; };
+
+    if checkpoints.for_level(&loaded.id).is_some() {
+        return;
+    }
+
+    if loaded.id == "08_security" {
+        camera_pans.send(CameraPan {
+            point: "boss".to_string(),
+            hold: Duration::from_millis(1500),
+        });
+    }
+}
@@ -1,4 +1,9 @@
 use super::level::current::LevelCommand;
+use super::level_progress::LevelList;
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
+use crate::app::console::ConsoleOutput;
+use crate::app::localization::Localization;
 use crate::utils::misc_utils::ExtendedEventReader;
 use bevy::prelude::*;
 
@@ -27,6 +32,11 @@ pub enum GameCommand {
         level_id: String,
     },
 
+    /// Start the game from the last checkpoint, if it was reached on that level
+    Continue {
+        level_id: String,
+    },
+
     Respawn,
 
     /// Delete all objects and stop the game
@@ -39,9 +49,15 @@ impl Plugin for GameStatesPlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<GameRunning>()
             .add_event::<GameCommand>()
+            .add_console_command("load", "LEVEL", "Start level, or switch to it if game is running")
+            .add_console_command("respawn", "", "Restart level from the last checkpoint")
+            .add_console_command("levels", "", "List level IDs")
             .add_systems(
                 Update,
-                execute_game_command.run_if(on_event::<GameCommand>()),
+                (
+                    execute_game_command.run_if(on_event::<GameCommand>()),
+                    console_command.run_if(on_event::<ConsoleCommand>()),
+                ),
             );
     }
 }
@@ -58,7 +74,7 @@ fn execute_game_command(
         let is_running = is_running.is_yes();
 
         match command {
-            GameCommand::Start { level_id } => {
+            GameCommand::Start { level_id } | GameCommand::Continue { level_id } => {
                 if is_running {
                     error!("game already running");
                     return;
@@ -87,3 +103,45 @@ fn execute_game_command(
         }
     }
 }
+
+fn console_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut game_commands: EventWriter<GameCommand>,
+    mut level_commands: EventWriter<LevelCommand>,
+    is_running: Res<State<GameRunning>>,
+    levels: Res<LevelList>,
+    loc: Res<Localization>,
+) {
+    for command in commands.read() {
+        match command.name.as_str() {
+            "load" => {
+                let result = command.arg::<String>(0).and_then(|level_id| {
+                    if !levels.all().any(|id| *id == level_id) {
+                        return Err(format!("no such level \"{level_id}\""));
+                    }
+
+                    // Start is ignored while game is running
+                    match is_running.is_yes() {
+                        true => level_commands.send(LevelCommand::Load(level_id.clone())),
+                        false => game_commands.send(GameCommand::Start {
+                            level_id: level_id.clone(),
+                        }),
+                    }
+                    Ok(format!("loading \"{level_id}\""))
+                });
+                output.send(ConsoleOutput::result(result));
+            }
+            "respawn" => match is_running.is_yes() {
+                true => game_commands.send(GameCommand::Respawn),
+                false => output.send(ConsoleOutput::error("game not running")),
+            },
+            "levels" => {
+                for id in levels.all() {
+                    output.send(ConsoleOutput::info(format!("{id} - {}", levels.name(id, &loc))));
+                }
+            }
+            _ => (),
+        }
+    }
+}
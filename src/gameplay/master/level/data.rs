@@ -1,3 +1,5 @@
+use crate::gameplay::master::checkpoints::Checkpoint;
+use crate::gameplay::master::objectives::Objective;
 use crate::gameplay::master::script_points::EnemySpawner;
 use crate::gameplay::master::script_points::ScriptPoint;
 use crate::gameplay::objects::barrels::Barrel;
@@ -32,6 +34,7 @@ pub struct LevelData {
     #[serde(serialize_with = "serde_sorted_map")]
     objects: HashMap<LevelObjectId, LevelObject>,
     last_object_id: u64,
+    objectives: Vec<Objective>,
 }
 
 impl LevelData {
@@ -57,6 +60,22 @@ impl LevelData {
     pub fn get_object(&self, id: LevelObjectId) -> Option<&LevelObject> {
         self.objects.get(&id)
     }
+
+    /// Area covered by tiles of all objects; none if level is empty
+    pub fn extents(&self) -> Option<Rect> {
+        self.objects
+            .values()
+            .map(|object| Rect::from_center_size(object.pos, Vec2::splat(TILE_SIZE)))
+            .reduce(|a, b| a.union(b))
+    }
+
+    /// Default objectives are used if level doesn't have any
+    pub fn objectives(&self) -> Vec<Objective> {
+        match self.objectives.is_empty() {
+            true => Objective::defaults(),
+            false => self.objectives.clone(),
+        }
+    }
 }
 
 /// Unique ID - doesn't get used again **in single executable run**.
@@ -153,6 +172,7 @@ pub enum LevelObjectData {
     ScriptPoint(ScriptPoint),
     EnemySpawner(EnemySpawner),
     Elevator(Elevator),
+    Checkpoint(Checkpoint),
 
     Barrel(Barrel),
     TerrainDecor(TerrainDecor),
@@ -30,6 +30,10 @@ impl GameObjectBundle {
     }
 }
 
+/// Level object which spawned this entity (i.e. enemy spawner)
+#[derive(Component, Clone, Copy)]
+pub struct SpawnedBy(pub LevelObjectId);
+
 /// Spawn an object.
 ///
 /// Exposed for use in level editor.
@@ -79,6 +83,9 @@ fn spawn_objects(mut spawn_objects: ResMut<Events<SpawnObject>>, mut commands: C
             LevelObjectData::Elevator(object) => {
                 entity.insert(object);
             }
+            LevelObjectData::Checkpoint(object) => {
+                entity.insert(object);
+            }
             LevelObjectData::EnemySpawner(object) => {
                 entity.insert(object);
             }
@@ -1,12 +1,24 @@
 use super::level::current::CurrentLevel;
-use super::level::current::LevelLoaded;
 use super::level::data::LevelData;
+use super::objectives::ObjectiveState;
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
+use crate::app::console::ConsoleOutput;
+use crate::app::localization::Localization;
+use crate::app::scheduling::SpawnSet;
 use crate::gameplay::mechanics::damage::Dead;
+use crate::gameplay::mechanics::overload::Overloaded;
+use crate::gameplay::objects::player::Player;
 use crate::gameplay::objects::player::PlayerEvent;
+use crate::presentation::Message;
+use crate::presentation::MessageCategory;
+use crate::utils::bevy::commands::FallibleCommands;
 use crate::utils::plugins::load_assets::LoadedTrackedAssets;
 use crate::utils::plugins::load_assets::TrackAssets;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
+use serde::Deserialize;
+use serde::Serialize;
 
 /// IDs of all levels in the game
 #[derive(Resource)]
@@ -40,11 +52,8 @@ impl LevelList {
             .data = data
     }
 
-    pub fn name(&self, id: &str) -> String {
-        self.levels
-            .get(id)
-            .map(|info| info.name.clone())
-            .unwrap_or_else(|| id.to_string())
+    pub fn name(&self, id: &str, loc: &Localization) -> String {
+        loc.get(&format!("level.{id}"))
     }
 
     fn next(&self, id: &str) -> Option<String> {
@@ -56,7 +65,6 @@ struct LevelInfo {
     asset: Handle<LevelData>,
     data: LevelData,
     next: Option<String>,
-    name: String,
 }
 
 /// Must be dealt with to pass the level
@@ -77,6 +85,8 @@ impl Plugin for LevelProgressPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<LevelProgressState>()
             .add_event::<GotoNextLevel>()
+            .add_console_command("unlock", "", "Unlock level exit")
+            .add_console_command("kill", "", "Overload all important enemies")
             .add_systems(Startup, load_levels)
             .add_systems(
                 First,
@@ -85,41 +95,42 @@ impl Plugin for LevelProgressPlugin {
             .add_systems(
                 Update,
                 (
-                    on_level_loaded.run_if(on_event::<LevelLoaded>()),
                     on_player_event.run_if(on_event::<PlayerEvent>()),
-                    check_enemies,
+                    unlock_exit,
+                    console_command.run_if(on_event::<ConsoleCommand>()),
                 ),
-            );
+            )
+            .add_systems(PostUpdate, reset_progress.in_set(SpawnSet::Controllers));
     }
 }
 
 fn load_levels(mut commands: Commands, mut track: TrackAssets) {
+    // names are localized, with "level.{id}" keys
     let sequence = [
-        ("01_cells", "Cells"),
-        ("02_connect", "Connector"),
-        ("03_loadbay", "Loading bay"),
-        ("04_storage", "Storage"),
-        ("05_process", "Processing"),
-        ("06_tunnels", "Fuel lines"),
-        ("07_engine", "Engine"),
-        ("08_security", "Bridge"),
+        "01_cells",
+        "02_connect",
+        "03_loadbay",
+        "04_storage",
+        "05_process",
+        "06_tunnels",
+        "07_engine",
+        "08_security",
     ];
 
     commands.insert_resource(LevelList {
-        first: sequence.first().unwrap().0.to_string(),
-        order: sequence.iter().map(|v| v.0.to_string()).collect(),
+        first: sequence.first().unwrap().to_string(),
+        order: sequence.iter().map(|id| id.to_string()).collect(),
         levels: sequence
             .iter()
             .enumerate()
-            .map(|(index, (id, name))| {
+            .map(|(index, id)| {
                 let asset = track.load_and_track(format!("levels/{id}.level"));
                 (
                     id.to_string(),
                     LevelInfo {
                         asset,
                         data: default(),
-                        next: sequence.get(index + 1).map(|s| s.0.to_string()),
-                        name: name.to_string(),
+                        next: sequence.get(index + 1).map(|id| id.to_string()),
                     },
                 )
             })
@@ -137,29 +148,82 @@ fn on_loaded_assets(mut levels: ResMut<LevelList>, mut assets: ResMut<Assets<Lev
     }
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LevelProgressState {
+    /// Exit is unlocked when all non-secondary objectives are completed
+    pub objectives: Vec<ObjectiveState>,
+
     exit_unlocked: bool,
     goto_sent: bool,
 
     pub green_lamp_hack: bool,
 }
 
-fn on_level_loaded(mut state: ResMut<LevelProgressState>) {
-    *state = default();
+impl LevelProgressState {
+    /// State to restore after respawn
+    pub fn snapshot(&self) -> Self {
+        Self {
+            objectives: self.objectives.clone(),
+            exit_unlocked: self.exit_unlocked,
+            goto_sent: false,
+            // lamp is respawned too
+            green_lamp_hack: self.exit_unlocked,
+        }
+    }
+
+    pub fn exit_unlocked(&self) -> bool {
+        self.exit_unlocked
+    }
+
+    /// Indices of completed objectives
+    pub fn completed(&self) -> impl Iterator<Item = usize> + '_ {
+        self.objectives
+            .iter()
+            .enumerate()
+            .filter_map(|(index, objective)| objective.completed.then_some(index))
+    }
+}
+
+/// Player is spawned exactly once per level (re)spawn
+fn reset_progress(
+    new: Query<(), Added<Player>>,
+    current: Res<CurrentLevel>,
+    mut state: ResMut<LevelProgressState>,
+) {
+    if new.is_empty() {
+        return;
+    }
+
+    *state = LevelProgressState {
+        objectives: current
+            .data
+            .objectives()
+            .into_iter()
+            .map(ObjectiveState::new)
+            .collect(),
+        ..default()
+    };
 }
 
 fn on_player_event(
     mut player_events: EventReader<PlayerEvent>,
     mut events: EventWriter<GotoNextLevel>,
+    mut messages: EventWriter<Message>,
     levels: Res<LevelList>,
     mut state: ResMut<LevelProgressState>,
     current: Res<CurrentLevel>,
+    loc: Res<Localization>,
 ) {
     for event in player_events.read() {
         match event {
             PlayerEvent::ReachedExitElevator => {
-                if state.exit_unlocked && !state.goto_sent {
+                if !state.exit_unlocked {
+                    messages.send(
+                        Message::notify(loc.get("message.warning"), loc.get("message.exit_locked"))
+                            .with_category(MessageCategory::Warning),
+                    );
+                } else if !state.goto_sent {
                     state.goto_sent = true;
 
                     let id = levels.next(&current.id);
@@ -175,15 +239,48 @@ fn on_player_event(
     }
 }
 
-fn check_enemies(
-    enemies: Query<Has<Dead>, With<ImportantEnemy>>,
+fn unlock_exit(mut state: ResMut<LevelProgressState>) {
+    if state.exit_unlocked {
+        return;
+    }
+
+    let all_completed = state
+        .objectives
+        .iter()
+        .filter(|objective| !objective.objective.secondary)
+        .all(|objective| objective.completed);
+    if all_completed {
+        state.exit_unlocked = true;
+        state.green_lamp_hack = true;
+    }
+}
+
+fn console_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
     mut state: ResMut<LevelProgressState>,
+    enemies: Query<Entity, (With<ImportantEnemy>, Without<Dead>)>,
+    mut entity_commands: Commands,
+    mut overloaded: EventWriter<Overloaded>,
 ) {
-    if !enemies.is_empty() && !state.exit_unlocked {
-        let all_dead = enemies.iter().all(|dead| dead);
-        if all_dead {
-            state.exit_unlocked = true;
-            state.green_lamp_hack = true;
+    for command in commands.read() {
+        match command.name.as_str() {
+            "unlock" => {
+                state.exit_unlocked = true;
+                state.green_lamp_hack = true;
+                output.send(ConsoleOutput::info("exit unlocked"));
+            }
+            "kill" => {
+                for entity in enemies.iter() {
+                    entity_commands.try_insert(entity, Dead);
+                    overloaded.send(Overloaded { entity });
+                }
+                output.send(ConsoleOutput::info(format!(
+                    "killed {} enemies",
+                    enemies.iter().count()
+                )));
+            }
+            _ => (),
         }
     }
 }
@@ -1,9 +1,11 @@
 use bevy::prelude::*;
 
+pub mod checkpoints;
 mod game_script;
 pub mod game_states;
 pub mod level;
 pub mod level_progress;
+pub mod objectives;
 pub mod script_points;
 pub mod time_master;
 
@@ -13,11 +15,13 @@ impl Plugin for MasterPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             game_states::GameStatesPlugin,
+            checkpoints::CheckpointsPlugin,
             time_master::TimeMasterPlugin,
             level::LevelPlugin,
             script_points::ScriptsPlugin,
             game_script::GameScriptPlugin,
             level_progress::LevelProgressPlugin,
+            objectives::ObjectivesPlugin,
         ));
     }
 }
@@ -0,0 +1,196 @@
+use super::level::data::TILE_SIZE;
+use super::level_progress::ImportantEnemy;
+use super::level_progress::LevelProgressState;
+use crate::app::localization::Localization;
+use crate::gameplay::mechanics::damage::Dead;
+use crate::gameplay::mechanics::overload::Overloaded;
+use crate::gameplay::mechanics::MechanicSet;
+use crate::gameplay::objects::barrels::Explosion;
+use crate::gameplay::objects::player::Player;
+use crate::presentation::Message;
+use crate::presentation::MessageCategory;
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Goal of the level, stored in level data
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Objective {
+    pub kind: ObjectiveKind,
+
+    /// Not required to unlock the exit
+    #[serde(default)]
+    pub secondary: bool,
+}
+
+impl Objective {
+    /// Used if level doesn't specify any
+    pub fn defaults() -> Vec<Self> {
+        vec![Self {
+            kind: ObjectiveKind::OverloadImportant,
+            secondary: false,
+        }]
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum ObjectiveKind {
+    /// All enemies with [`ImportantEnemy`] must be overloaded
+    OverloadImportant,
+    /// Overload that many enemies of any kind
+    OverloadEnemies(u32),
+    /// Blow up that many barrels
+    DestroyBarrels(u32),
+    /// Player must get close to the point
+    ReachPoint { pos: Vec2, name: String },
+    /// Player must stay alive for that many seconds
+    Survive(f32),
+}
+
+/// Progress of the objective in current level
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ObjectiveState {
+    pub objective: Objective,
+    pub progress: f32,
+    pub required: f32,
+    /// Stays set even if progress decreases
+    pub completed: bool,
+}
+
+impl ObjectiveState {
+    pub fn new(objective: Objective) -> Self {
+        let required = match &objective.kind {
+            ObjectiveKind::OverloadImportant => 0.,
+            ObjectiveKind::OverloadEnemies(count) | ObjectiveKind::DestroyBarrels(count) => {
+                *count as f32
+            }
+            ObjectiveKind::ReachPoint { .. } => 1.,
+            ObjectiveKind::Survive(seconds) => *seconds,
+        };
+        Self {
+            objective,
+            progress: 0.,
+            required,
+            completed: false,
+        }
+    }
+
+    /// Description with progress
+    pub fn text(&self, loc: &Localization) -> String {
+        let (progress, required) = (self.progress as u32, self.required as u32);
+        let text = match &self.objective.kind {
+            ObjectiveKind::OverloadImportant => loc.format(
+                "objective.overload_important",
+                &[("progress", &progress), ("required", &required)],
+            ),
+            ObjectiveKind::OverloadEnemies(_) => loc.format(
+                "objective.overload_enemies",
+                &[("progress", &progress), ("required", &required)],
+            ),
+            ObjectiveKind::DestroyBarrels(_) => loc.format(
+                "objective.destroy_barrels",
+                &[("progress", &progress), ("required", &required)],
+            ),
+            ObjectiveKind::ReachPoint { name, .. } => {
+                loc.format("objective.reach_point", &[("name", name)])
+            }
+            ObjectiveKind::Survive(_) => loc.format(
+                "objective.survive",
+                &[("progress", &progress), ("required", &required)],
+            ),
+        };
+        match self.objective.secondary {
+            true => loc.format("objective.optional", &[("objective", &text)]),
+            false => text,
+        }
+    }
+}
+
+/// Sent when objective is completed
+#[derive(Event)]
+pub struct ObjectiveCompleted(pub ObjectiveState);
+
+/// Objective point is reached if player is that close to it
+const REACH_RADIUS: f32 = TILE_SIZE;
+
+pub struct ObjectivesPlugin;
+
+impl Plugin for ObjectivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ObjectiveCompleted>()
+            .add_systems(
+                FixedUpdate,
+                update_objectives.after(MechanicSet::PostReaction),
+            )
+            .add_systems(
+                Update,
+                announce_objectives.run_if(on_event::<ObjectiveCompleted>()),
+            );
+    }
+}
+
+fn update_objectives(
+    important: Query<Has<Dead>, With<ImportantEnemy>>,
+    player: Query<&GlobalTransform, (With<Player>, Without<Dead>)>,
+    mut overloaded: EventReader<Overloaded>,
+    mut explosions: EventReader<Explosion>,
+    mut state: ResMut<LevelProgressState>,
+    mut completed: EventWriter<ObjectiveCompleted>,
+    time: Res<Time>,
+) {
+    let overloaded = overloaded.read().count() as f32;
+    let explosions = explosions.read().count() as f32;
+    let player_pos = player
+        .get_single()
+        .ok()
+        .map(|pos| pos.translation().truncate());
+
+    for objective in state.objectives.iter_mut() {
+        if objective.completed {
+            continue;
+        }
+
+        match &objective.objective.kind {
+            ObjectiveKind::OverloadImportant => {
+                objective.required = important.iter().count() as f32;
+                objective.progress = important.iter().filter(|dead| *dead).count() as f32;
+            }
+            ObjectiveKind::OverloadEnemies(_) => objective.progress += overloaded,
+            ObjectiveKind::DestroyBarrels(_) => objective.progress += explosions,
+            ObjectiveKind::ReachPoint { pos, .. } => {
+                if player_pos.is_some_and(|player| player.distance(*pos) < REACH_RADIUS) {
+                    objective.progress = 1.;
+                }
+            }
+            ObjectiveKind::Survive(_) => {
+                if player_pos.is_some() {
+                    objective.progress += time.delta_seconds();
+                }
+            }
+        }
+
+        // required is zero if there are no important enemies (yet)
+        objective.completed = objective.required > 0. && objective.progress >= objective.required;
+        if objective.completed {
+            info!("objective completed: {:?}", objective.objective.kind);
+            completed.send(ObjectiveCompleted(objective.clone()));
+        }
+    }
+}
+
+fn announce_objectives(
+    mut completed: EventReader<ObjectiveCompleted>,
+    mut messages: EventWriter<Message>,
+    loc: Res<Localization>,
+) {
+    for ObjectiveCompleted(objective) in completed.read() {
+        let text = loc.format(
+            "objective.completed",
+            &[("objective", &objective.text(&loc))],
+        );
+        messages.send(
+            Message::notify(loc.get("message.objective"), text)
+                .with_category(MessageCategory::Objective),
+        );
+    }
+}
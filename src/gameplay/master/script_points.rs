@@ -1,4 +1,6 @@
+use super::level::data::LevelObjectId;
 use super::level::spawn::GameObjectBundle;
+use super::level::spawn::SpawnedBy;
 use super::level_progress::ImportantEnemy;
 use crate::gameplay::mechanics::overload::MagicEmitter;
 use crate::gameplay::mechanics::overload::Overload;
@@ -34,10 +36,10 @@ impl Plugin for ScriptsPlugin {
 }
 
 fn on_point_added(
-    new: Query<(&GlobalTransform, &ScriptPoint), Added<ScriptPoint>>,
+    new: Query<(&GlobalTransform, &ScriptPoint, &LevelObjectId), Added<ScriptPoint>>,
     mut commands: Commands,
 ) {
-    for (transform, point) in new.iter() {
+    for (transform, point, id) in new.iter() {
         match point.id.as_str() {
             "player" => {
                 commands.spawn((
@@ -51,6 +53,7 @@ fn on_point_added(
                     Overload::new(100.),
                     ImportantEnemy,
                     MagicEmitter,
+                    SpawnedBy(*id),
                 ));
             }
             id => warn!("unknown script point id \"{id}\""),
@@ -58,12 +61,20 @@ fn on_point_added(
     }
 }
 
-fn enemy_spawner(new: Query<(Entity, &EnemySpawner), Added<EnemySpawner>>, mut commands: Commands) {
-    for (entity, spawner) in new.iter() {
+fn enemy_spawner(
+    new: Query<(Entity, &EnemySpawner, &LevelObjectId), Added<EnemySpawner>>,
+    mut commands: Commands,
+) {
+    for (entity, spawner, id) in new.iter() {
         match *spawner {
             EnemySpawner::Regular => {
-                commands.try_with_children(entity, |parent| {
-                    parent.spawn((GameObjectBundle::new("enemy", default()), Enemy::Important));
+                let id = *id;
+                commands.try_with_children(entity, move |parent| {
+                    parent.spawn((
+                        GameObjectBundle::new("enemy", default()),
+                        Enemy::Important,
+                        SpawnedBy(id),
+                    ));
                 });
             }
         }
@@ -1,18 +1,162 @@
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
+use crate::app::console::ConsoleOutput;
 use bevy::prelude::*;
+use bevy::time::TimeSystem;
+use std::time::Duration;
+
+/// Temporary change of game speed, see [`TimeMaster::push`]
+struct TimeModifier {
+    name: &'static str,
+    scale: f64,
+    /// How fast scale changes to and from this value, per second
+    ramp_rate: f64,
+    /// Real time left before modifier is removed
+    left: Option<Duration>,
+}
 
 /// Parameters controlling in-game time
-#[derive(Resource, Default)]
+#[derive(Resource)]
 pub struct TimeMaster {
     pub in_menu: bool,
     pub in_editor: bool,
+
+    /// Multiplier for game speed, set from console
+    pub speed: f64,
+    /// Debug: run game at half speed
+    pub half_speed: bool,
+    /// Debug: stop game time, but not menus. See [`TimeMaster::step`].
+    pub debug_pause: bool,
+    /// Fixed timesteps to run while in debug pause, one per frame
+    step_fixed: u32,
+
+    /// Slowest one is used
+    modifiers: Vec<TimeModifier>,
+    /// Current scale from modifiers; changes smoothly
+    scale: f64,
+    ramp_rate: f64,
+}
+
+impl Default for TimeMaster {
+    fn default() -> Self {
+        Self {
+            in_menu: false,
+            in_editor: false,
+            speed: 1.,
+            half_speed: false,
+            debug_pause: false,
+            step_fixed: 0,
+            modifiers: vec![],
+            scale: 1.,
+            ramp_rate: f64::INFINITY,
+        }
+    }
 }
 
 impl TimeMaster {
+    /// Add modifier, or replace one with the same name.
+    /// Scale changes to the new value over `ramp` (and back over the same time when removed).
+    pub fn push(&mut self, name: &'static str, scale: f64, ramp: Duration) {
+        self.push_modifier(name, scale, ramp, None)
+    }
+
+    /// Same as [`Self::push`], but modifier is removed after `duration` of unpaused real time
+    pub fn push_for(&mut self, name: &'static str, scale: f64, ramp: Duration, duration: Duration) {
+        self.push_modifier(name, scale, ramp, Some(duration))
+    }
+
+    /// Does nothing if there is no such modifier
+    pub fn remove(&mut self, name: &'static str) {
+        if let Some(index) = self.modifiers.iter().position(|m| m.name == name) {
+            self.ramp_rate = self.modifiers.remove(index).ramp_rate;
+        }
+    }
+
+    /// Current multiplier from modifiers, without [`Self::speed`] and debug settings
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Names and scales of active modifiers
+    pub fn modifiers(&self) -> impl Iterator<Item = (&'static str, f64)> + '_ {
+        self.modifiers.iter().map(|m| (m.name, m.scale))
+    }
+
+    /// Enables debug pause and runs game for that many fixed timesteps
+    pub fn step(&mut self, steps: u32) {
+        self.debug_pause = true;
+        self.step_fixed += steps;
+    }
+
+    fn push_modifier(
+        &mut self,
+        name: &'static str,
+        scale: f64,
+        ramp: Duration,
+        left: Option<Duration>,
+    ) {
+        let scale = scale.max(0.);
+        let ramp_rate = match ramp.as_secs_f64() {
+            secs if secs > 0. => (1. - scale).abs().max(0.01) / secs,
+            _ => f64::INFINITY,
+        };
+
+        self.modifiers.retain(|m| m.name != name);
+        self.modifiers.push(TimeModifier {
+            name,
+            scale,
+            ramp_rate,
+            left,
+        });
+        self.ramp_rate = ramp_rate;
+    }
+
+    fn target_scale(&self) -> f64 {
+        self.modifiers
+            .iter()
+            .map(|m| m.scale)
+            .reduce(f64::min)
+            .unwrap_or(1.)
+    }
+
+    fn paused(&self) -> bool {
+        self.in_menu || self.in_editor || self.debug_pause
+    }
+
+    /// Expires modifiers and moves scale towards target. Returns true if any modifier was removed.
+    fn update_scale(&mut self, delta: Duration) -> bool {
+        let count = self.modifiers.len();
+        let mut ramp_rate = self.ramp_rate;
+
+        self.modifiers.retain_mut(|m| match &mut m.left {
+            Some(left) if *left <= delta => {
+                ramp_rate = m.ramp_rate;
+                false
+            }
+            Some(left) => {
+                *left -= delta;
+                true
+            }
+            None => true,
+        });
+        self.ramp_rate = ramp_rate;
+
+        let target = self.target_scale();
+        let step = self.ramp_rate * delta.as_secs_f64();
+        self.scale = match target - self.scale {
+            diff if diff.abs() <= step => target,
+            diff => self.scale + step.copysign(diff),
+        };
+
+        self.modifiers.len() != count
+    }
+
     fn factor(&self) -> f64 {
-        if self.in_menu || self.in_editor {
+        if self.paused() {
             0.
         } else {
-            1.
+            let half_speed = if self.half_speed { 0.5 } else { 1. };
+            self.speed * half_speed * self.scale
         }
     }
 }
@@ -21,13 +165,109 @@ pub struct TimeMasterPlugin;
 
 impl Plugin for TimeMasterPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<TimeMaster>().add_systems(
-            Last,
-            update_time_factor.run_if(resource_changed::<TimeMaster>()),
-        );
+        app.init_resource::<TimeMaster>()
+            .add_console_command("speed", "[FACTOR]", "Show or set game speed multiplier")
+            .add_console_command("half_speed", "", "Toggle half speed mode")
+            .add_console_command("pause", "", "Toggle game pause (without opening menu)")
+            .add_console_command("step", "[STEPS]", "Pause game and advance it by fixed steps")
+            .add_console_command(
+                "slowmo",
+                "[SCALE] [SECONDS]",
+                "Add time modifier (or remove it if no arguments)",
+            )
+            .add_systems(Update, console_command.run_if(on_event::<ConsoleCommand>()))
+            .add_systems(First, step_fixed_time.after(TimeSystem))
+            .add_systems(Last, update_time_factor);
     }
 }
 
-fn update_time_factor(control: Res<TimeMaster>, mut time: ResMut<Time<Virtual>>) {
-    time.set_relative_speed_f64(control.factor());
+fn update_time_factor(
+    mut control: ResMut<TimeMaster>,
+    mut time: ResMut<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+) {
+    // ramps use real time, so they aren't affected by themselves.
+    // Ramping itself doesn't trigger change detection, only expiration does.
+    if !control.paused() && control.bypass_change_detection().update_scale(real_time.delta()) {
+        control.set_changed();
+    }
+
+    let factor = control.factor();
+    if time.relative_speed_f64() != factor {
+        time.set_relative_speed_f64(factor);
+    }
+}
+
+/// Advances virtual time by exactly the amount needed for one [`FixedUpdate`] step.
+/// Virtual time is stopped during debug pause, so nothing else advances it.
+fn step_fixed_time(
+    mut control: ResMut<TimeMaster>,
+    mut time: ResMut<Time>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    if !control.debug_pause || control.step_fixed == 0 || control.in_menu || control.in_editor {
+        return;
+    }
+    control.step_fixed -= 1;
+
+    virtual_time.advance_by(fixed_time.timestep().saturating_sub(fixed_time.overstep()));
+    *time = virtual_time.as_generic();
+}
+
+fn console_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut control: ResMut<TimeMaster>,
+) {
+    for command in commands.read() {
+        if command.is("speed") {
+            output.send(ConsoleOutput::result(command.arg_opt::<f64>(0).and_then(
+                |speed| match speed {
+                    Some(speed) if !(0. ..=100.).contains(&speed) => {
+                        Err("speed must be in 0..100 range".to_string())
+                    }
+                    Some(speed) => {
+                        control.speed = speed;
+                        Ok(format!("speed set to {speed}"))
+                    }
+                    None => Ok(format!("speed is {}", control.speed)),
+                },
+            )));
+        } else if command.is("half_speed") {
+            control.half_speed = !control.half_speed;
+            output.send(ConsoleOutput::info(format!("half speed: {}", control.half_speed)));
+        } else if command.is("pause") {
+            control.debug_pause = !control.debug_pause;
+            control.step_fixed = 0;
+            output.send(ConsoleOutput::info(format!("paused: {}", control.debug_pause)));
+        } else if command.is("step") {
+            match command.arg_opt::<u32>(0) {
+                Ok(steps) => control.step(steps.unwrap_or(1)),
+                Err(error) => output.send(ConsoleOutput::error(error)),
+            }
+        } else if command.is("slowmo") {
+            let args = command
+                .arg_opt::<f64>(0)
+                .and_then(|scale| Ok((scale, command.arg_opt::<f64>(1)?)));
+            output.send(ConsoleOutput::result(args.and_then(|args| match args {
+                (Some(scale), _) if !(0. ..=100.).contains(&scale) => {
+                    Err("scale must be in 0..100 range".to_string())
+                }
+                (Some(scale), Some(seconds)) if seconds > 0. => {
+                    let duration = Duration::from_secs_f64(seconds);
+                    control.push_for("console", scale, Duration::from_millis(300), duration);
+                    Ok(format!("scale set to {scale} for {seconds} seconds"))
+                }
+                (Some(scale), _) => {
+                    control.push("console", scale, Duration::from_millis(300));
+                    Ok(format!("scale set to {scale}"))
+                }
+                (None, _) => {
+                    control.remove("console");
+                    Ok("console modifier removed".to_string())
+                }
+            })));
+        }
+    }
 }
@@ -0,0 +1,253 @@
+use super::damage::Dead;
+use super::MechanicSet;
+use crate::app::localization::Localization;
+use crate::app::scores::Scores;
+use crate::gameplay::balance::Balance;
+use crate::utils::misc_utils::serde_duration_secs;
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Player ability
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum Ability {
+    Fire,
+    Pull,
+    Kick,
+    Dash,
+}
+
+impl Ability {
+    /// Upgrade level can't be higher than that
+    pub const MAX_LEVEL: u32 = 2;
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        [Self::Fire, Self::Pull, Self::Kick, Self::Dash].into_iter()
+    }
+
+    pub fn name(self, loc: &Localization) -> String {
+        loc.get(match self {
+            Ability::Fire => "ability.fire",
+            Ability::Pull => "ability.pull",
+            Ability::Kick => "ability.kick",
+            Ability::Dash => "ability.dash",
+        })
+    }
+
+    /// What upgrade to that level does
+    pub fn upgrade_description(self, level: u32, loc: &Localization) -> String {
+        loc.get(match (self, level) {
+            (Ability::Fire, 1) => "upgrade.fire_1",
+            (Ability::Fire, _) => "upgrade.fire_2",
+            (Ability::Pull, 1) => "upgrade.pull_1",
+            (Ability::Pull, _) => "upgrade.pull_2",
+            (Ability::Kick, 1) => "upgrade.kick_1",
+            (Ability::Kick, _) => "upgrade.kick_2",
+            (Ability::Dash, 1) => "upgrade.dash_1",
+            (Ability::Dash, _) => "upgrade.dash_2",
+        })
+    }
+
+    /// Used if not specified in balance file
+    pub fn default_stats(self) -> AbilityStats {
+        let (cooldown, max_level_cooldown, energy_cost) = match self {
+            Ability::Fire => (300, 300, 7.),
+            Ability::Pull => (200, 200, 15.),
+            Ability::Kick => (500, 500, 5.),
+            Ability::Dash => (1200, 800, 20.),
+        };
+        AbilityStats {
+            cooldown: Duration::from_millis(cooldown),
+            max_level_cooldown: Duration::from_millis(max_level_cooldown),
+            energy_cost,
+        }
+    }
+}
+
+/// Per-ability values, stored in [`Balance`]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AbilityStats {
+    #[serde(with = "serde_duration_secs")]
+    pub cooldown: Duration,
+    /// Cooldown at [`Ability::MAX_LEVEL`]
+    #[serde(with = "serde_duration_secs")]
+    pub max_level_cooldown: Duration,
+    pub energy_cost: f32,
+}
+
+impl Default for AbilityStats {
+    fn default() -> Self {
+        Ability::Fire.default_stats()
+    }
+}
+
+impl AbilityStats {
+    pub fn cooldown(&self, level: u32) -> Duration {
+        match level >= Ability::MAX_LEVEL {
+            true => self.max_level_cooldown,
+            false => self.cooldown,
+        }
+    }
+}
+
+/// Upgrade levels of the abilities, saved in [`Scores`]
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AbilityUpgrades {
+    levels: BTreeMap<Ability, u32>,
+
+    /// Unspent upgrade points, received for completing levels
+    pub points: u32,
+}
+
+impl AbilityUpgrades {
+    pub fn level(&self, ability: Ability) -> u32 {
+        self.levels.get(&ability).copied().unwrap_or_default()
+    }
+
+    pub fn can_upgrade(&self, ability: Ability) -> bool {
+        self.points != 0 && self.level(ability) < Ability::MAX_LEVEL
+    }
+
+    /// Returns false if upgrade is not possible
+    pub fn upgrade(&mut self, ability: Ability) -> bool {
+        if !self.can_upgrade(ability) {
+            return false;
+        }
+        self.points -= 1;
+        *self.levels.entry(ability).or_default() += 1;
+        true
+    }
+}
+
+/// Resource spent by abilities, regenerates over time
+#[derive(Component)]
+pub struct Energy {
+    pub current: f32,
+    pub max: f32,
+    /// Per second
+    pub regen: f32,
+}
+
+impl Energy {
+    pub fn new(balance: &Balance) -> Self {
+        Self {
+            current: balance.player_energy,
+            max: balance.player_energy,
+            regen: balance.player_energy_regen,
+        }
+    }
+}
+
+/// Cooldowns and upgrade levels of the abilities.
+///
+/// Levels are updated from [`Scores`] automatically.
+#[derive(Component, Default)]
+pub struct Abilities {
+    states: BTreeMap<Ability, AbilityState>,
+}
+
+#[derive(Default)]
+struct AbilityState {
+    level: u32,
+    cooldown: Timer,
+}
+
+impl Abilities {
+    pub fn new(upgrades: &AbilityUpgrades) -> Self {
+        let mut abilities = Self::default();
+        abilities.set_levels(upgrades);
+        abilities
+    }
+
+    pub fn level(&self, ability: Ability) -> u32 {
+        self.states
+            .get(&ability)
+            .map(|state| state.level)
+            .unwrap_or_default()
+    }
+
+    /// Cooldown has passed and there is enough energy
+    pub fn is_ready(&self, ability: Ability, energy: &Energy, balance: &Balance) -> bool {
+        let state = self.states.get(&ability);
+        let cooldown = state.map(|v| v.cooldown.finished()).unwrap_or(true);
+        cooldown && energy.current >= balance.ability(ability).energy_cost
+    }
+
+    /// Time left until ability can be used, normalized to `[0; 1]` range
+    pub fn t_cooldown(&self, ability: Ability) -> f32 {
+        self.states
+            .get(&ability)
+            .filter(|state| !state.cooldown.finished())
+            .map(|state| state.cooldown.percent_left())
+            .unwrap_or_default()
+    }
+
+    /// Starts cooldown and spends energy. Returns false if ability isn't ready.
+    pub fn try_use(&mut self, ability: Ability, energy: &mut Energy, balance: &Balance) -> bool {
+        if !self.is_ready(ability, energy, balance) {
+            return false;
+        }
+
+        let stats = balance.ability(ability);
+        let state = self.states.entry(ability).or_default();
+        state.cooldown = Timer::new(stats.cooldown(state.level), TimerMode::Once);
+        energy.current -= stats.energy_cost;
+        true
+    }
+
+    fn set_levels(&mut self, upgrades: &AbilityUpgrades) {
+        for ability in Ability::all() {
+            self.states.entry(ability).or_default().level = upgrades.level(ability);
+        }
+    }
+}
+
+pub struct AbilitiesPlugin;
+
+impl Plugin for AbilitiesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, update_abilities.in_set(MechanicSet::Input))
+            .add_systems(
+                Update,
+                (
+                    update_levels.run_if(resource_changed::<Scores>()),
+                    update_energy.run_if(resource_changed::<Balance>()),
+                ),
+            );
+    }
+}
+
+pub fn update_abilities(
+    mut entities: Query<(&mut Abilities, Option<&mut Energy>), Without<Dead>>,
+    time: Res<Time>,
+) {
+    for (mut abilities, energy) in entities.iter_mut() {
+        for state in abilities.states.values_mut() {
+            state.cooldown.tick(time.delta());
+        }
+
+        if let Some(mut energy) = energy {
+            energy.current = (energy.current + energy.regen * time.delta_seconds()).min(energy.max);
+        }
+    }
+}
+
+fn update_levels(mut entities: Query<&mut Abilities>, scores: Res<Scores>) {
+    for mut abilities in entities.iter_mut() {
+        abilities.set_levels(&scores.upgrades);
+    }
+}
+
+/// Apply changed balance to live entities
+fn update_energy(mut entities: Query<&mut Energy>, balance: Res<Balance>) {
+    for mut energy in entities.iter_mut() {
+        let Energy { max, regen, .. } = Energy::new(&balance);
+        energy.max = max;
+        energy.regen = regen;
+        energy.current = energy.current.min(max);
+    }
+}
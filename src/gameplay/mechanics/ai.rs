@@ -1,13 +1,17 @@
 use super::damage::Dead;
 use super::damage::Projectile;
+use super::status::StatusEffects;
+use super::status::StatusKind;
 use super::MechanicSet;
 use crate::app::scheduling::SpawnSet;
+use crate::gameplay::balance::Balance;
 use crate::gameplay::master::level::data::TILE_SIZE;
 use crate::gameplay::objects::player::Player;
 use crate::gameplay::physics::PhysicsType;
 use crate::gameplay::utils::RotateToTarget;
 use crate::utils::bevy::commands::FallibleCommands;
 use crate::utils::math_algorithms::map_linear_range;
+use crate::utils::plugins::profiler::ProfileScope;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use std::time::Duration;
@@ -18,36 +22,37 @@ pub struct Target {
     /// Has line-of-sight to target
     found: Option<TargetData>,
 
-    visible_for: Duration,
-    invisible_for: Duration,
+    pub visible_for: Duration,
+    pub invisible_for: Duration,
 }
 
 impl Target {
     /// Max visibility distance
     const MAX_DISTANCE: f32 = TILE_SIZE * 6.;
 
-    /// AI has delayed reaction to player appearing in their field of view
-    const MIN_REACTION_DELAY: Duration = Duration::from_millis(1000);
-    const MAX_REACTION_DELAY: Duration = Duration::from_millis(2000);
-
     /// Time in which AI forgets it has seen the player
     const FORGET_TIME: Duration = Duration::from_millis(2000);
 
     /// Can react to player - delay time has passed
-    fn can_react(&self) -> bool {
+    fn can_react(&self, balance: &Balance) -> bool {
         let Some(data) = self.found else { return false; };
 
         let time = map_linear_range(
             data.distance,
             0.,
             Self::MAX_DISTANCE,
-            Self::MIN_REACTION_DELAY.as_secs_f32(),
-            Self::MAX_REACTION_DELAY.as_secs_f32(),
+            balance.enemy_reaction_delay_min.as_secs_f32(),
+            balance.enemy_reaction_delay_max.as_secs_f32(),
             true,
         );
 
         self.visible_for >= Duration::from_secs_f32(time)
     }
+
+    /// Distance to the target, if it's visible
+    pub fn distance(&self) -> Option<f32> {
+        self.found.map(|data| data.distance)
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -74,7 +79,7 @@ impl Plugin for AiPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(PostUpdate, spawn.in_set(SpawnSet::Controllers))
             .add_systems(
-                Update,
+                FixedUpdate,
                 (find_target, rotate, shoot)
                     .chain()
                     .in_set(MechanicSet::Action),
@@ -94,6 +99,8 @@ fn find_target(
     phy_world: Res<RapierContext>,
     time: Res<Time>,
 ) {
+    let _scope = ProfileScope::new("find_target");
+
     let target_pos = target
         .get_single()
         .map(|pos| pos.translation().truncate())
@@ -144,13 +151,26 @@ fn rotate(mut entities: Query<(&mut RotateToTarget, &Target), Without<Dead>>) {
     }
 }
 
-fn shoot(
-    mut shooters: Query<(&Shoot, &mut ShootState, &Target, &GlobalTransform), Without<Dead>>,
-    mut commands: Commands,
-    time: Res<Time>,
-) {
-    for (shoot, mut state, target, pos) in shooters.iter_mut() {
-        let Some(target) = target.found.filter(|_| target.can_react()) else { continue; };
+/// Enemies which can shoot, unless stunned
+type Shooters<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Shoot,
+        &'static mut ShootState,
+        &'static Target,
+        &'static GlobalTransform,
+        Option<&'static StatusEffects>,
+    ),
+    Without<Dead>,
+>;
+
+fn shoot(mut shooters: Shooters, mut commands: Commands, time: Res<Time>, balance: Res<Balance>) {
+    for (shoot, mut state, target, pos, status) in shooters.iter_mut() {
+        if status.map(|v| v.has(StatusKind::Stunned)).unwrap_or(false) {
+            continue;
+        }
+        let Some(target) = target.found.filter(|_| target.can_react(&balance)) else { continue; };
 
         state.cooldown.tick(time.delta());
 
@@ -161,7 +181,7 @@ fn shoot(
             commands.spawn(
                 shoot
                     .projectile
-                    .bundle(pos.translation().truncate(), target.dir),
+                    .bundle(&balance, pos.translation().truncate(), target.dir),
             );
         }
     }
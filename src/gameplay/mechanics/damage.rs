@@ -1,5 +1,7 @@
+use super::status::ApplyStatus;
+use super::status::StatusEffect;
 use super::MechanicSet;
-use crate::gameplay::balance::DURATION_FIREBALL;
+use crate::gameplay::balance::Balance;
 use crate::gameplay::master::level::spawn::GameObjectBundle;
 use crate::gameplay::physics::*;
 use crate::gameplay::utils::rotation_from_dir;
@@ -15,14 +17,16 @@ pub struct Projectile {
     pub speed: f32,
     pub radius: f32,
     pub ty: DamageType,
+    /// Applied to the victim on hit
+    pub status: Option<StatusEffect>,
 }
 
 impl Projectile {
     /// Game object bundle for moving projectile
-    pub fn bundle(self, position: Vec2, direction: Vec2) -> impl Bundle {
+    pub fn bundle(self, balance: &Balance, position: Vec2, direction: Vec2) -> impl Bundle {
         let lifetime = match self.ty {
             DamageType::Player => Duration::from_millis(5000),
-            DamageType::Barrels => DURATION_FIREBALL,
+            DamageType::Barrels => balance.explosion_fireball_duration,
         };
         (
             GameObjectBundle::new(
@@ -46,7 +50,7 @@ impl Projectile {
 }
 
 /// Damage is applied only if damage type and health type match
-#[derive(Component, Clone, Copy, PartialEq, Eq)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum DamageType {
     Player,
     Barrels,
@@ -66,6 +70,31 @@ impl Health {
     }
 }
 
+/// Projectiles of that type don't affect the entity while this component exists.
+/// Other damage (i.e. explosions and burning) still applies. Removed automatically.
+#[derive(Component)]
+pub struct Invulnerable {
+    pub ty: DamageType,
+    timer: Timer,
+}
+
+impl Invulnerable {
+    pub fn new(ty: DamageType, duration: Duration) -> Self {
+        Self {
+            ty,
+            timer: Timer::new(duration, TimerMode::Once),
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.timer.remaining()
+    }
+}
+
+/// Entity ignores all damage. Set from console.
+#[derive(Component)]
+pub struct GodMode;
+
 /// Added when health (or its equivavient) reaches zero.
 ///
 /// Colliders are removed on death.
@@ -96,10 +125,13 @@ impl Plugin for DamagePlugin {
         app.add_event::<ProjectileImpact>()
             .add_event::<ApplyDamage>()
             .add_systems(
-                Update,
-                (projectile, apply_damage)
-                    .chain()
-                    .in_set(MechanicSet::Reaction),
+                FixedUpdate,
+                (
+                    (projectile, apply_damage)
+                        .chain()
+                        .in_set(MechanicSet::Reaction),
+                    update_invulnerable.after(MechanicSet::Reaction),
+                ),
             )
             .add_systems(PostUpdate, remove_dead_colliders);
     }
@@ -110,14 +142,30 @@ fn projectile(
     mut commands: Commands,
     mut impacts: EventWriter<ProjectileImpact>,
     mut apply_damage: EventWriter<ApplyDamage>,
+    mut apply_status: EventWriter<ApplyStatus>,
+    invulnerable: Query<&Invulnerable>,
 ) {
     for (proj_entity, projectile, colliding, pos) in projectiles.iter() {
         for victim in colliding.iter() {
+            if invulnerable
+                .get(victim)
+                .is_ok_and(|v| v.ty == projectile.ty)
+            {
+                continue;
+            }
+
             apply_damage.send(ApplyDamage {
                 victim,
                 amount: projectile.damage,
                 ty: projectile.ty,
-            })
+            });
+
+            if let Some(effect) = projectile.status {
+                apply_status.send(ApplyStatus {
+                    entity: victim,
+                    effect,
+                });
+            }
         }
 
         if !colliding.is_empty() {
@@ -133,11 +181,14 @@ fn projectile(
 
 fn apply_damage(
     mut damage: EventReader<ApplyDamage>,
-    mut victims: Query<&mut Health, Without<Dead>>,
+    mut victims: Query<(&mut Health, Has<GodMode>), Without<Dead>>,
     mut commands: Commands,
 ) {
     for damage in damage.read() {
-        if let Ok(mut health) = victims.get_mut(damage.victim) {
+        if let Ok((mut health, god_mode)) = victims.get_mut(damage.victim) {
+            if god_mode {
+                continue;
+            }
             if health.ty == damage.ty && health.reduce(damage.amount) {
                 commands.try_insert(damage.victim, Dead);
             }
@@ -145,9 +196,54 @@ fn apply_damage(
     }
 }
 
+fn update_invulnerable(
+    mut entities: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut invulnerable) in entities.iter_mut() {
+        if invulnerable.timer.tick(time.delta()).finished() {
+            commands.try_remove::<Invulnerable>(entity);
+        }
+    }
+}
+
 fn remove_dead_colliders(entity: Query<Entity, Added<Dead>>, mut commands: Commands) {
     for entity in entity.iter() {
         commands.try_remove::<RigidBody>(entity);
         commands.try_remove::<Collider>(entity);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invulnerable_still_takes_explosion_damage() {
+        let mut app = App::new();
+        app.add_event::<ApplyDamage>()
+            .add_systems(Update, apply_damage);
+
+        // same as during a dash
+        let player = app
+            .world
+            .spawn((
+                Health {
+                    value: 10,
+                    ty: DamageType::Player,
+                },
+                Invulnerable::new(DamageType::Player, Duration::from_secs(1)),
+            ))
+            .id();
+
+        app.world.send_event(ApplyDamage {
+            victim: player,
+            amount: 3,
+            ty: DamageType::Player,
+        });
+        app.update();
+
+        assert_eq!(app.world.get::<Health>(player).unwrap().value, 7);
+    }
+}
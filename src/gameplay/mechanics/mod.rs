@@ -1,11 +1,15 @@
+use super::physics::InterpolationSet;
+use super::physics::PhysicsSet;
 use bevy::prelude::*;
 
+pub mod abilities;
 pub mod ai;
 pub mod damage;
 pub mod movement;
 pub mod overload;
+pub mod status;
 
-/// In [`Update`]
+/// In [`FixedUpdate`], before physics step
 #[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum MechanicSet {
     /// Processes external input or sensor data
@@ -25,20 +29,24 @@ pub struct MechanicsPlugin;
 impl Plugin for MechanicsPlugin {
     fn build(&self, app: &mut App) {
         app.configure_sets(
-            Update,
+            FixedUpdate,
             (
                 MechanicSet::Input,
                 MechanicSet::Action,
                 MechanicSet::Reaction,
                 MechanicSet::PostReaction,
             )
-                .chain(),
+                .chain()
+                .after(InterpolationSet::Restore)
+                .before(PhysicsSet::SyncBackend),
         )
         .add_plugins((
             movement::MovementPlugin,
             damage::DamagePlugin,
             ai::AiPlugin,
             overload::OverloadPlugin,
+            abilities::AbilitiesPlugin,
+            status::StatusPlugin,
         ));
     }
 }
@@ -1,3 +1,4 @@
+use super::status::StatusEffects;
 use super::MechanicSet;
 use crate::gameplay::physics::*;
 use bevy::prelude::*;
@@ -13,8 +14,14 @@ pub struct MovementController {
     /// [`Self::target_dir`] is multiplied by this
     pub speed: f32,
 
+    /// Used instead of [`Self::speed`] and status effects (i.e. during dash), reset each step
+    pub speed_override: Option<f32>,
+
     /// Unitless force coefficient - larger it is, more responsive movement is
     pub k_force: f32,
+
+    /// Velocity of the surface entity is standing on (i.e. conveyor belt), reset each step
+    pub ground_velocity: Vec2,
 }
 
 impl MovementController {
@@ -33,7 +40,9 @@ impl Default for MovementController {
         Self {
             target_dir: default(),
             speed: 6.,
+            speed_override: None,
             k_force: 150.,
+            ground_velocity: Vec2::ZERO,
         }
     }
 }
@@ -42,7 +51,7 @@ pub struct MovementPlugin;
 
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_controller.in_set(MechanicSet::Action));
+        app.add_systems(FixedUpdate, update_controller.in_set(MechanicSet::Action));
     }
 }
 
@@ -52,14 +61,21 @@ fn update_controller(
         &Velocity,
         &mut ExternalForce,
         &ReadMassProperties,
+        Option<&StatusEffects>,
     )>,
     time: Res<Time>,
 ) {
-    for (mut controller, velocity, mut ext_force, mass) in controllers.iter_mut() {
-        let target_velocity = std::mem::take(&mut controller.target_dir) * controller.speed;
+    for (mut controller, velocity, mut ext_force, mass, status) in controllers.iter_mut() {
+        let speed = match controller.speed_override.take() {
+            Some(speed) => speed,
+            None => controller.speed * status.map(|v| v.speed_multiplier()).unwrap_or(1.),
+        };
+
+        let target_velocity = std::mem::take(&mut controller.target_dir) * speed
+            + std::mem::take(&mut controller.ground_velocity);
         let velocity = velocity.linvel;
 
-        let k_force = controller.k_force * controller.speed * time.delta_seconds();
+        let k_force = controller.k_force * speed * time.delta_seconds();
 
         let force = (target_velocity - velocity) * mass.mass * k_force;
         ext_force.force = force;
@@ -1,9 +1,11 @@
 use super::damage::Dead;
-use crate::gameplay::balance::OVERLOAD_RADIUS;
+use super::status::StatusEffects;
+use super::status::StatusKind;
+use super::MechanicSet;
+use crate::gameplay::balance::Balance;
 use crate::gameplay::physics::*;
 use crate::utils::bevy::commands::FallibleCommands;
-use bevy::diagnostic::DiagnosticsStore;
-use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
+use crate::utils::plugins::profiler::ProfileScope;
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -27,6 +29,12 @@ impl Overload {
     }
 }
 
+/// Sent when entity becomes [`Dead`] from overload
+#[derive(Event)]
+pub struct Overloaded {
+    pub entity: Entity,
+}
+
 #[derive(Component)]
 pub struct MagicRecepient {
     pub count: usize,
@@ -39,7 +47,11 @@ pub struct OverloadPlugin;
 
 impl Plugin for OverloadPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (update_overload, boss_magic));
+        app.add_event::<Overloaded>()
+            .add_systems(
+                FixedUpdate,
+                ((update_overload, overload_death).chain(), boss_magic).in_set(MechanicSet::Input),
+            );
     }
 }
 
@@ -66,25 +78,24 @@ fn update_overload(
             Entity,
             &GlobalTransform,
             &mut Overload,
-            Option<&MagicRecepient>,
+            Option<&StatusEffects>,
         ),
         Without<Dead>,
     >,
     sources: Query<&OverloadSource>,
     physics: Res<RapierContext>,
-    mut commands: Commands,
-    diagnostics: Res<DiagnosticsStore>,
-    emitters: Query<(), (With<MagicEmitter>, Without<Dead>)>,
+    mut step: Local<u32>,
+    balance: Res<Balance>,
 ) {
-    let frame_count = diagnostics
-        .get(FrameTimeDiagnosticsPlugin::FRAME_COUNT)
-        .and_then(|diag| diag.value())
-        .unwrap_or_default() as u32;
+    let _scope = ProfileScope::new("update_overload");
+
+    // each entity is updated every fourth step
+    *step = step.wrapping_add(1);
 
-    let shape = Collider::ball(OVERLOAD_RADIUS);
+    let shape = Collider::ball(balance.overload_radius);
 
-    for (entity, pos, mut overload, magic) in overloads.iter_mut() {
-        if entity.index() & 3 != frame_count & 3 {
+    for (entity, pos, mut overload, status) in overloads.iter_mut() {
+        if entity.index() & 3 != *step & 3 {
             continue;
         }
 
@@ -103,6 +114,20 @@ fn update_overload(
             },
         );
 
+        if let Some(status) = status {
+            let burning = status.stacks(StatusKind::Burning) as f32;
+            overload.current += burning * balance.overload_burning_status;
+        }
+    }
+}
+
+fn overload_death(
+    mut overloads: Query<(Entity, &mut Overload, Option<&MagicRecepient>), Without<Dead>>,
+    emitters: Query<(), (With<MagicEmitter>, Without<Dead>)>,
+    mut commands: Commands,
+    mut overloaded: EventWriter<Overloaded>,
+) {
+    for (entity, mut overload, magic) in overloads.iter_mut() {
         if let Some(magic) = magic {
             let emitters = emitters.iter().count();
             if emitters > magic.count {
@@ -112,6 +137,7 @@ fn update_overload(
 
         if overload.current >= overload.max {
             commands.try_insert(entity, Dead);
+            overloaded.send(Overloaded { entity });
         }
     }
 }
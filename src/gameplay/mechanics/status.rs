@@ -0,0 +1,260 @@
+use super::damage::ApplyDamage;
+use super::damage::Dead;
+use super::damage::Health;
+use super::MechanicSet;
+use crate::app::localization::Localization;
+use crate::gameplay::balance::Balance;
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Kind of the timed status effect
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum StatusKind {
+    /// Deals damage over time (or adds overload). Stacks intensity.
+    Burning,
+    /// Disables [`super::ai::Shoot`]. Longest duration wins.
+    Stunned,
+    /// Scales [`super::movement::MovementController::speed`]. Strongest effect wins.
+    Slowed,
+}
+
+impl StatusKind {
+    pub fn all() -> impl Iterator<Item = Self> {
+        [Self::Burning, Self::Stunned, Self::Slowed].into_iter()
+    }
+
+    pub fn name(self, loc: &Localization) -> String {
+        loc.get(match self {
+            StatusKind::Burning => "status.burning",
+            StatusKind::Stunned => "status.stunned",
+            StatusKind::Slowed => "status.slowed",
+        })
+    }
+
+    /// How many times effect can stack
+    fn max_stacks(self) -> u32 {
+        match self {
+            StatusKind::Burning => 3,
+            StatusKind::Stunned | StatusKind::Slowed => 1,
+        }
+    }
+}
+
+/// Description of the effect to apply
+#[derive(Clone, Copy, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub duration: Duration,
+
+    /// Meaning depends on the kind:
+    /// damage per second per stack for burning, speed multiplier for slowed.
+    pub strength: f32,
+}
+
+impl StatusEffect {
+    pub fn burning(balance: &Balance) -> Self {
+        Self {
+            kind: StatusKind::Burning,
+            duration: balance.status_burning_duration,
+            strength: balance.status_burning_damage,
+        }
+    }
+
+    pub fn stunned(balance: &Balance) -> Self {
+        Self {
+            kind: StatusKind::Stunned,
+            duration: balance.status_stunned_duration,
+            strength: 1.,
+        }
+    }
+
+    pub fn slowed(balance: &Balance) -> Self {
+        Self {
+            kind: StatusKind::Slowed,
+            duration: balance.status_slowed_duration,
+            strength: balance.status_slowed_speed,
+        }
+    }
+}
+
+/// Active status effects of the entity. Only entities with this component can be affected.
+///
+/// Effects are cleared on death.
+#[derive(Component, Default)]
+pub struct StatusEffects {
+    active: Vec<ActiveStatus>,
+}
+
+struct ActiveStatus {
+    effect: StatusEffect,
+    stacks: u32,
+    timer: Timer,
+
+    /// Fractional damage not yet applied
+    damage: f32,
+}
+
+impl StatusEffects {
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    /// Zero if effect is not active
+    pub fn stacks(&self, kind: StatusKind) -> u32 {
+        self.get(kind).map(|v| v.stacks).unwrap_or_default()
+    }
+
+    /// Time left until effect ends, normalized to `[0; 1]` range
+    pub fn t_left(&self, kind: StatusKind) -> f32 {
+        self.get(kind)
+            .map(|v| v.timer.percent_left())
+            .unwrap_or_default()
+    }
+
+    /// Multiplier for movement speed
+    pub fn speed_multiplier(&self) -> f32 {
+        self.get(StatusKind::Slowed)
+            .map(|v| v.effect.strength)
+            .unwrap_or(1.)
+    }
+
+    /// Returns true if effect wasn't active before
+    fn apply(&mut self, effect: StatusEffect) -> bool {
+        let Some(status) = self.active.iter_mut().find(|v| v.effect.kind == effect.kind) else {
+            self.active.push(ActiveStatus {
+                effect,
+                stacks: 1,
+                timer: Timer::new(effect.duration, TimerMode::Once),
+                damage: 0.,
+            });
+            return true;
+        };
+
+        let stacks = (status.stacks + 1).min(effect.kind.max_stacks());
+        let duration = status.timer.remaining().max(effect.duration);
+
+        let strength = &mut status.effect.strength;
+        match effect.kind {
+            StatusKind::Burning => *strength = strength.max(effect.strength),
+            StatusKind::Stunned => (),
+            // lower multiplier is stronger
+            StatusKind::Slowed => *strength = strength.min(effect.strength),
+        }
+
+        status.stacks = stacks;
+        status.timer = Timer::new(duration, TimerMode::Once);
+        false
+    }
+
+    fn get(&self, kind: StatusKind) -> Option<&ActiveStatus> {
+        self.active.iter().find(|v| v.effect.kind == kind)
+    }
+}
+
+/// Send this to apply status effect to an entity
+#[derive(Event)]
+pub struct ApplyStatus {
+    pub entity: Entity,
+    pub effect: StatusEffect,
+}
+
+/// Sent when effect starts or ends. Not sent when stacks or duration change.
+#[derive(Event)]
+pub struct StatusChanged {
+    pub entity: Entity,
+    pub kind: StatusKind,
+    pub active: bool,
+}
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyStatus>()
+            .add_event::<StatusChanged>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    (apply_status, update_status)
+                        .chain()
+                        .in_set(MechanicSet::Reaction),
+                    clear_dead.after(MechanicSet::PostReaction),
+                ),
+            );
+    }
+}
+
+fn apply_status(
+    mut apply: EventReader<ApplyStatus>,
+    mut entities: Query<&mut StatusEffects, Without<Dead>>,
+    mut changed: EventWriter<StatusChanged>,
+) {
+    for ApplyStatus { entity, effect } in apply.read() {
+        if let Ok(mut status) = entities.get_mut(*entity) {
+            if status.apply(*effect) {
+                changed.send(StatusChanged {
+                    entity: *entity,
+                    kind: effect.kind,
+                    active: true,
+                });
+            }
+        }
+    }
+}
+
+fn update_status(
+    mut entities: Query<(Entity, &mut StatusEffects, Option<&Health>), Without<Dead>>,
+    mut changed: EventWriter<StatusChanged>,
+    mut damage: EventWriter<ApplyDamage>,
+    time: Res<Time>,
+) {
+    for (entity, mut status, health) in entities.iter_mut() {
+        for active in status.active.iter_mut() {
+            active.timer.tick(time.delta());
+
+            if active.effect.kind == StatusKind::Burning {
+                if let Some(health) = health {
+                    active.damage +=
+                        active.effect.strength * active.stacks as f32 * time.delta_seconds();
+
+                    let amount = active.damage.floor();
+                    if amount >= 1. {
+                        active.damage -= amount;
+                        damage.send(ApplyDamage {
+                            victim: entity,
+                            amount: amount as u32,
+                            ty: health.ty,
+                        });
+                    }
+                }
+            }
+        }
+
+        status.active.retain(|active| {
+            let finished = active.timer.finished();
+            if finished {
+                changed.send(StatusChanged {
+                    entity,
+                    kind: active.effect.kind,
+                    active: false,
+                });
+            }
+            !finished
+        });
+    }
+}
+
+fn clear_dead(
+    mut entities: Query<(Entity, &mut StatusEffects), Added<Dead>>,
+    mut changed: EventWriter<StatusChanged>,
+) {
+    for (entity, mut status) in entities.iter_mut() {
+        for active in status.active.drain(..) {
+            changed.send(StatusChanged {
+                entity,
+                kind: active.effect.kind,
+                active: false,
+            });
+        }
+    }
+}
@@ -12,6 +12,7 @@ pub struct GameplayPlugin;
 impl Plugin for GameplayPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
+            balance::BalancePlugin,
             master::MasterPlugin,
             objects::ObjectsPlugin,
             mechanics::MechanicsPlugin,
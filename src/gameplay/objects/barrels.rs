@@ -1,15 +1,30 @@
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
+use crate::app::console::ConsoleOutput;
 use crate::app::scheduling::SpawnSet;
-use crate::gameplay::balance::OVERLOAD_BURNING_BARREL;
+use crate::gameplay::balance::Balance;
+use crate::gameplay::master::level::spawn::GameObjectBundle;
+use crate::gameplay::master::time_master::TimeMaster;
+use crate::gameplay::mechanics::ai::Shoot;
+use crate::gameplay::mechanics::damage::ApplyDamage;
 use crate::gameplay::mechanics::damage::DamageType;
 use crate::gameplay::mechanics::damage::Dead;
 use crate::gameplay::mechanics::damage::Health;
 use crate::gameplay::mechanics::overload::OverloadSource;
+use crate::gameplay::mechanics::status::ApplyStatus;
+use crate::gameplay::mechanics::status::StatusEffect;
+use crate::gameplay::mechanics::status::StatusEffects;
 use crate::gameplay::mechanics::MechanicSet;
 use crate::gameplay::physics::*;
 use crate::utils::bevy::commands::FallibleCommands;
+use crate::utils::math_algorithms::lerp;
+use crate::utils::math_algorithms::map_linear_range;
+use crate::utils::plugins::profiler::ProfileScope;
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::VecDeque;
 use std::time::Duration;
 
 /// A barrel. Can be grabbed and set on fire.
@@ -22,7 +37,7 @@ pub enum Barrel {
 #[derive(Component)]
 pub struct OnFire {
     pub started_at: Duration,
-    explode_at: Duration,
+    pub explode_at: Duration,
 }
 
 /// Sent when barrel explodes
@@ -38,10 +53,23 @@ pub struct BarrelsPlugin;
 impl Plugin for BarrelsPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<Explosion>()
+            .add_console_command("barrel", "", "Spawn fire barrel at cursor")
             .add_systems(PostUpdate, spawn_barrels.in_set(SpawnSet::Roots))
+            .add_systems(
+                FixedUpdate,
+                (
+                    put_barrels_on_fire,
+                    (explode_barrels, (explosion_shockwave, explosion_ignition)).chain(),
+                )
+                    .in_set(MechanicSet::PostReaction),
+            )
             .add_systems(
                 Update,
-                (put_barrels_on_fire, explode_barrels).in_set(MechanicSet::PostReaction),
+                (
+                    update_balance.run_if(resource_changed::<Balance>()),
+                    console_command.run_if(on_event::<ConsoleCommand>()),
+                    chain_explosion_slowmo,
+                ),
             );
     }
 }
@@ -66,31 +94,50 @@ fn spawn_barrels(new: Query<Entity, Added<Barrel>>, mut commands: Commands) {
                     value: BARREL_HEALTH,
                     ty: DamageType::Barrels,
                 },
+                StatusEffects::default(),
             ),
         );
     }
 }
 
+/// Barrel explodes after specified time. `now` is [`Time<Fixed>`] elapsed.
+pub fn ignite(
+    commands: &mut Commands,
+    balance: &Balance,
+    entity: Entity,
+    now: Duration,
+    explode_after: Duration,
+) {
+    commands.try_insert(
+        entity,
+        (
+            OnFire {
+                started_at: now,
+                explode_at: now + explode_after,
+            },
+            OverloadSource {
+                power: balance.overload_burning_barrel,
+            },
+        ),
+    );
+}
+
 fn put_barrels_on_fire(
     barrels: Query<(Entity, &Health, &Barrel), (Changed<Health>, Without<OnFire>)>,
     mut commands: Commands,
     time: Res<Time>,
+    balance: Res<Balance>,
 ) {
     for (entity, health, barrel) in barrels.iter() {
         match barrel {
             Barrel::Fire => {
                 if health.value != BARREL_HEALTH {
-                    commands.try_insert(
+                    ignite(
+                        &mut commands,
+                        &balance,
                         entity,
-                        (
-                            OnFire {
-                                started_at: time.elapsed(),
-                                explode_at: time.elapsed() + ON_FIRE_DURATION,
-                            },
-                            OverloadSource {
-                                power: OVERLOAD_BURNING_BARREL,
-                            },
-                        ),
+                        time.elapsed(),
+                        ON_FIRE_DURATION,
                     );
                 }
             }
@@ -118,3 +165,207 @@ fn explode_barrels(
         })
     }
 }
+
+/// Brief slow-motion when a lot of barrels explode at once
+fn chain_explosion_slowmo(
+    mut explosions: EventReader<Explosion>,
+    mut recent: Local<VecDeque<Duration>>,
+    mut time_master: ResMut<TimeMaster>,
+    time: Res<Time>,
+    balance: Res<Balance>,
+) {
+    let now = time.elapsed();
+    recent.extend(explosions.read().map(|_| now));
+    while recent
+        .front()
+        .is_some_and(|at| now.saturating_sub(*at) > balance.slowmo_explosion_window)
+    {
+        recent.pop_front();
+    }
+
+    if balance.slowmo_explosions != 0 && recent.len() >= balance.slowmo_explosions as usize {
+        recent.clear();
+        time_master.push_for(
+            "chain_explosion",
+            balance.slowmo_scale as f64,
+            balance.slowmo_ramp,
+            balance.slowmo_duration,
+        );
+    }
+}
+
+/// Barrels which can be ignited by explosions
+type IgnitableBarrels<'w, 's> =
+    Query<'w, 's, &'static GlobalTransform, (With<Barrel>, Without<OnFire>, Without<Dead>)>;
+
+/// Explosion doesn't affect anything behind the walls
+fn explosion_occluded(physics: &RapierContext, at: Vec2, delta: Vec2) -> bool {
+    physics
+        .cast_ray(at, delta, 1., false, PhysicsType::WallOnly.filter())
+        .is_some()
+}
+
+/// Bodies affected by explosion; stun only matters for shooting enemies
+type ShockwaveBodies<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        &'static RigidBody,
+        Has<Health>,
+        Has<Barrel>,
+        Has<Shoot>,
+    ),
+>;
+
+/// Impulse, damage and status effects
+fn explosion_shockwave(
+    mut explosions: EventReader<Explosion>,
+    mut commands: Commands,
+    mut damage: EventWriter<ApplyDamage>,
+    mut status: EventWriter<ApplyStatus>,
+    physics: Res<RapierContext>,
+    bodies: ShockwaveBodies,
+    balance: Res<Balance>,
+) {
+    let _scope = ProfileScope::new("explosion_shockwave");
+
+    let shape = Collider::ball(balance.explosion_radius);
+
+    // summed, so simultaneous explosions in a chain reaction add up
+    let mut impulses: HashMap<Entity, Vec2> = default();
+
+    for Explosion { at, .. } in explosions.read() {
+        let pos = at.truncate();
+
+        physics.intersections_with_shape(pos, 0., &shape, PhysicsType::Object.filter(), |entity| {
+            let Ok((transform, body, has_health, is_barrel, can_shoot)) = bodies.get(entity) else {
+                return true;
+            };
+
+            let delta = transform.translation().truncate() - pos;
+            let distance = delta.length();
+            if distance >= balance.explosion_radius || explosion_occluded(&physics, pos, delta) {
+                return true;
+            }
+
+            let t = distance / balance.explosion_radius;
+
+            if *body == RigidBody::Dynamic {
+                let impulse = delta.normalize_or_zero() * balance.explosion_impulse * (1. - t);
+                *impulses.entry(entity).or_default() += impulse;
+            }
+
+            // barrels are ignited separately
+            if !is_barrel {
+                let effect = StatusEffect::burning(&balance);
+                status.send(ApplyStatus { entity, effect });
+            }
+            if can_shoot {
+                let effect = StatusEffect::stunned(&balance);
+                status.send(ApplyStatus { entity, effect });
+            }
+
+            if has_health {
+                let falloff = lerp(1., balance.explosion_damage_falloff, t);
+                for (amount, ty) in [
+                    (balance.explosion_damage_barrels, DamageType::Barrels),
+                    (balance.explosion_damage_player, DamageType::Player),
+                ] {
+                    damage.send(ApplyDamage {
+                        victim: entity,
+                        amount: (amount * falloff).round() as u32,
+                        ty,
+                    });
+                }
+            }
+
+            true
+        });
+    }
+
+    for (entity, impulse) in impulses {
+        commands.try_insert(
+            entity,
+            ExternalImpulse {
+                impulse,
+                ..default()
+            },
+        );
+    }
+}
+
+/// Chain ignition of nearby barrels, farther ones explode later
+fn explosion_ignition(
+    mut explosions: EventReader<Explosion>,
+    mut commands: Commands,
+    physics: Res<RapierContext>,
+    barrels: IgnitableBarrels,
+    time: Res<Time>,
+    balance: Res<Balance>,
+) {
+    let shape = Collider::ball(balance.explosion_ignition_radius);
+
+    for Explosion { at, .. } in explosions.read() {
+        let pos = at.truncate();
+
+        physics.intersections_with_shape(pos, 0., &shape, PhysicsType::Object.filter(), |entity| {
+            let Ok(transform) = barrels.get(entity) else { return true; };
+
+            let delta = transform.translation().truncate() - pos;
+            let distance = delta.length();
+            if distance >= balance.explosion_ignition_radius
+                || explosion_occluded(&physics, pos, delta)
+            {
+                return true;
+            }
+
+            let delay = map_linear_range(
+                distance,
+                0.,
+                balance.explosion_ignition_radius,
+                balance.explosion_ignition_delay_min.as_secs_f32(),
+                balance.explosion_ignition_delay_max.as_secs_f32(),
+                true,
+            );
+            ignite(
+                &mut commands,
+                &balance,
+                entity,
+                time.elapsed(),
+                Duration::from_secs_f32(delay),
+            );
+
+            true
+        });
+    }
+}
+
+/// Apply changed overload power to burning barrels
+fn update_balance(
+    mut barrels: Query<&mut OverloadSource, (With<Barrel>, With<OnFire>)>,
+    balance: Res<Balance>,
+) {
+    for mut source in barrels.iter_mut() {
+        source.power = balance.overload_burning_barrel;
+    }
+}
+
+fn console_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut entity_commands: Commands,
+) {
+    for command in commands.read().filter(|command| command.is("barrel")) {
+        let Some(pos) = command.cursor else {
+            output.send(ConsoleOutput::error("cursor is outside of the world"));
+            continue;
+        };
+
+        entity_commands.spawn((
+            GameObjectBundle::new("barrel", Transform::from_translation(pos.extend(0.))),
+            Barrel::Fire,
+        ));
+        output.send(ConsoleOutput::info(format!("spawned barrel at {pos}")));
+    }
+}
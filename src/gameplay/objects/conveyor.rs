@@ -1,16 +1,21 @@
 use super::barrels::Barrel;
+use super::enemy::Enemy;
 use super::player::Player;
 use crate::app::scheduling::SpawnSet;
 use crate::gameplay::master::level::data::HALF_TILE;
 use crate::gameplay::master::level::data::TILE_SIZE;
+use crate::gameplay::master::level::spawn::GameObject;
 use crate::gameplay::master::level::spawn::GameObjectBundle;
 use crate::gameplay::mechanics::damage::Dead;
+use crate::gameplay::mechanics::movement::MovementController;
+use crate::gameplay::mechanics::MechanicSet;
 use crate::gameplay::physics::*;
 use crate::utils::bevy::commands::FallibleCommands;
+use crate::utils::bevy::misc_utils::ExtendedTimer;
 use crate::utils::math_algorithms::rotate_vec2;
+use crate::utils::plugins::profiler::ProfileScope;
 use crate::utils::random::RandomRange;
 use bevy::prelude::*;
-use bevy::transform::TransformSystem;
 use bevy::utils::HashSet;
 use serde::Deserialize;
 use serde::Serialize;
@@ -18,47 +23,308 @@ use std::f32::consts::FRAC_PI_2;
 use std::time::Duration;
 
 #[derive(Component, Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(from = "ConveyorData", into = "ConveyorData")]
 pub enum Conveyor {
+    Belt {
+        /// Units per second
+        speed: f32,
+
+        /// Switch channel which changes behavior of the belt
+        channel: Option<u8>,
+
+        /// What happens when the channel is active
+        switched: BeltSwitch,
+    },
+    StartChute(ChuteSchedule),
+    EndChute,
+
+    /// Floor plate, toggles its channel when something steps on it
+    Switch {
+        channel: u8,
+    },
+}
+
+/// How [`Conveyor`] is stored in level files.
+///
+/// Default belt is stored as plain `Belt`, same as before belts had parameters,
+/// so older levels can still be loaded.
+#[derive(Serialize, Deserialize)]
+enum ConveyorData {
     Belt,
-    StartChute(ConveyorOutput),
+    ConfiguredBelt {
+        #[serde(default = "default_belt_speed")]
+        speed: f32,
+        #[serde(default)]
+        channel: Option<u8>,
+        #[serde(default)]
+        switched: BeltSwitch,
+    },
+    StartChute(ChuteSchedule),
     EndChute,
+    Switch {
+        channel: u8,
+    },
+}
+
+impl From<ConveyorData> for Conveyor {
+    fn from(data: ConveyorData) -> Self {
+        match data {
+            ConveyorData::Belt => Self::DEFAULT_BELT,
+            ConveyorData::ConfiguredBelt {
+                speed,
+                channel,
+                switched,
+            } => Self::Belt {
+                speed,
+                channel,
+                switched,
+            },
+            ConveyorData::StartChute(schedule) => Self::StartChute(schedule),
+            ConveyorData::EndChute => Self::EndChute,
+            ConveyorData::Switch { channel } => Self::Switch { channel },
+        }
+    }
+}
+
+impl From<Conveyor> for ConveyorData {
+    fn from(conveyor: Conveyor) -> Self {
+        match conveyor {
+            conveyor if conveyor == Conveyor::DEFAULT_BELT => Self::Belt,
+            Conveyor::Belt {
+                speed,
+                channel,
+                switched,
+            } => Self::ConfiguredBelt {
+                speed,
+                channel,
+                switched,
+            },
+            Conveyor::StartChute(schedule) => Self::StartChute(schedule),
+            Conveyor::EndChute => Self::EndChute,
+            Conveyor::Switch { channel } => Self::Switch { channel },
+        }
+    }
+}
+
+impl Conveyor {
+    pub const DEFAULT_BELT: Self = Self::Belt {
+        speed: DEFAULT_BELT_SPEED,
+        channel: None,
+        switched: BeltSwitch::Reverse,
+    };
+}
+
+/// Belt behavior when its switch channel is active
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub enum BeltSwitch {
+    /// Move in the opposite direction
+    #[default]
+    Reverse,
+
+    /// Junction: route items to the left side
+    TurnLeft,
+
+    /// Junction: route items to the right side
+    TurnRight,
+
+    Stop,
+}
+
+impl BeltSwitch {
+    /// Direction of the belt when switched, if it doesn't stop
+    fn direction(self, forward: Vec2) -> Option<Vec2> {
+        match self {
+            BeltSwitch::Reverse => Some(-forward),
+            BeltSwitch::TurnLeft => Some(forward.perp()),
+            BeltSwitch::TurnRight => Some(-forward.perp()),
+            BeltSwitch::Stop => None,
+        }
+    }
+}
+
+const DEFAULT_BELT_SPEED: f32 = 3.;
+
+fn default_belt_speed() -> f32 {
+    DEFAULT_BELT_SPEED
+}
+
+/// Intervals in [`ChuteSchedule`] are clamped to that, in seconds
+const MAX_CHUTE_INTERVAL: f32 = 3600.;
+
+/// What and when [`Conveyor::StartChute`] spawns
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct ChuteSchedule {
+    /// Objects with their relative weights
+    pub objects: Vec<(ChuteObject, u32)>,
+
+    /// Min and max number of objects in a burst
+    pub burst: (u32, u32),
+
+    /// Min and max delay between bursts, in seconds
+    pub burst_interval: (f32, f32),
+
+    /// Min and max delay between objects in a burst, in seconds
+    pub spawn_interval: (f32, f32),
+
+    /// Spawning is paused while that many spawned objects exist
+    pub max_alive: Option<u32>,
+
+    pub trigger: ChuteTrigger,
+}
+
+impl Default for ChuteSchedule {
+    fn default() -> Self {
+        ChutePreset::BarrelsRareGroup.schedule()
+    }
+}
+
+impl ChuteSchedule {
+    fn random_object(&self) -> Option<ChuteObject> {
+        let total: u32 = self.objects.iter().map(|v| v.1).sum();
+        let mut value = (0..total).random();
+
+        for (object, weight) in &self.objects {
+            if value < *weight {
+                return Some(*object);
+            }
+            value -= weight;
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum ChuteObject {
+    Barrel(Barrel),
+    Enemy(Enemy),
 }
 
+/// When [`Conveyor::StartChute`] is working
 #[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Default)]
-pub enum ConveyorOutput {
+pub enum ChuteTrigger {
     #[default]
-    BarrelsRareGroup,
+    Always,
+
+    /// While switch channel is active
+    Switch(u8),
+
+    /// While player is closer than that distance
+    PlayerNear(f32),
+}
 
+/// Typical schedules, for the editor
+#[derive(Clone, Copy, Debug)]
+pub enum ChutePreset {
+    BarrelsRareGroup,
     BarrelsFrequent,
     RandomEnemies,
     Random,
 }
 
+impl ChutePreset {
+    pub fn all() -> impl Iterator<Item = Self> {
+        [
+            Self::BarrelsRareGroup,
+            Self::BarrelsFrequent,
+            Self::RandomEnemies,
+            Self::Random,
+        ]
+        .into_iter()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ChutePreset::BarrelsRareGroup => "Barrels rare group",
+            ChutePreset::BarrelsFrequent => "Barrels frequent",
+            ChutePreset::RandomEnemies => "Random enemies",
+            ChutePreset::Random => "Random",
+        }
+    }
+
+    pub fn schedule(self) -> ChuteSchedule {
+        let barrel = (ChuteObject::Barrel(Barrel::Fire), 1);
+        let enemy = (ChuteObject::Enemy(Enemy::Spam), 1);
+
+        match self {
+            ChutePreset::BarrelsRareGroup => ChuteSchedule {
+                objects: vec![barrel],
+                burst: (4, 4),
+                burst_interval: (6., 6.),
+                spawn_interval: (0.4, 0.4),
+                max_alive: None,
+                trigger: ChuteTrigger::Always,
+            },
+            ChutePreset::BarrelsFrequent => ChuteSchedule {
+                objects: vec![barrel],
+                burst: (3, 3),
+                burst_interval: (2., 2.),
+                spawn_interval: (0.8, 0.8),
+                max_alive: None,
+                trigger: ChuteTrigger::Always,
+            },
+            ChutePreset::RandomEnemies => ChuteSchedule {
+                objects: vec![enemy],
+                burst: (1, 1),
+                burst_interval: (5., 12.),
+                spawn_interval: (0., 0.),
+                max_alive: Some(3),
+                trigger: ChuteTrigger::Always,
+            },
+            ChutePreset::Random => ChuteSchedule {
+                objects: vec![(barrel.0, 9), enemy],
+                burst: (1, 3),
+                burst_interval: (2., 6.),
+                spawn_interval: (4., 20.),
+                max_alive: None,
+                trigger: ChuteTrigger::Always,
+            },
+        }
+    }
+}
+
+/// State of the [`Conveyor::Switch`]
+#[derive(Component)]
+pub struct ConveyorSwitch {
+    pub channel: u8,
+    pub active: bool,
+
+    /// Something is standing on the switch
+    pressed: bool,
+}
+
 pub struct ConveyorPlugin;
 
 impl Plugin for ConveyorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            PostUpdate,
-            (
-                spawn_conveyor.in_set(SpawnSet::Roots),
-                (belt_move, chute_spawn, chute_destroy)
-                    .after(PhysicsSet::Writeback)
-                    .before(TransformSystem::TransformPropagate),
-            ),
-        );
+        app.add_systems(PostUpdate, spawn_conveyor.in_set(SpawnSet::Roots))
+            .add_systems(
+                FixedUpdate,
+                (update_switches, belt_move)
+                    .chain()
+                    .in_set(MechanicSet::Input),
+            )
+            .add_systems(
+                FixedUpdate,
+                (chute_spawn, chute_destroy).after(PhysicsSet::Writeback),
+            );
     }
 }
 
 #[derive(Component)]
-struct Belt;
+struct Belt {
+    speed: f32,
+    channel: Option<u8>,
+    switched: BeltSwitch,
+}
 
-#[derive(Component, Default)]
+#[derive(Component)]
 struct ChuteSpawn {
-    single_after: Duration,
-    burst_after: Duration,
-    burst_left: usize,
-    output: ConveyorOutput,
+    schedule: ChuteSchedule,
+    cooldown: Timer,
+    burst_left: u32,
+    /// Spawned objects, including already destroyed ones
+    spawned: Vec<Entity>,
 }
 
 #[derive(Component)]
@@ -67,7 +333,11 @@ struct ChuteDespawn;
 fn spawn_conveyor(new: Query<(Entity, &Conveyor), Added<Conveyor>>, mut commands: Commands) {
     for (entity, object) in new.iter() {
         match *object {
-            Conveyor::Belt => {
+            Conveyor::Belt {
+                speed,
+                channel,
+                switched,
+            } => {
                 commands.try_insert(
                     entity,
                     (
@@ -79,17 +349,23 @@ fn spawn_conveyor(new: Query<(Entity, &Conveyor), Added<Conveyor>>, mut commands
                         CollidingEntities::default(),
                         ActiveEvents::COLLISION_EVENTS,
                         //
-                        Belt,
+                        Belt {
+                            speed,
+                            channel,
+                            switched,
+                        },
                     ),
                 );
             }
 
-            Conveyor::StartChute(output) => {
+            Conveyor::StartChute(ref schedule) => {
                 commands.try_insert(
                     entity,
                     ChuteSpawn {
-                        output,
-                        ..default()
+                        schedule: schedule.clone(),
+                        cooldown: Timer::once(Duration::ZERO),
+                        burst_left: 0,
+                        spawned: default(),
                     },
                 );
             }
@@ -114,49 +390,126 @@ fn spawn_conveyor(new: Query<(Entity, &Conveyor), Added<Conveyor>>, mut commands
                     ),
                 );
             }
+
+            Conveyor::Switch { channel } => {
+                commands.try_insert(
+                    entity,
+                    (
+                        RigidBody::Fixed,
+                        Collider::cuboid(HALF_TILE * 0.6, HALF_TILE * 0.6),
+                        Sensor,
+                        //
+                        PhysicsType::Conveyor.groups(),
+                        CollidingEntities::default(),
+                        ActiveEvents::COLLISION_EVENTS,
+                        //
+                        ConveyorSwitch {
+                            channel,
+                            active: false,
+                            pressed: false,
+                        },
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn update_switches(mut switches: Query<(&CollidingEntities, &mut ConveyorSwitch)>) {
+    for (colliding, mut switch) in switches.iter_mut() {
+        let pressed = !colliding.is_empty();
+        if pressed && !switch.pressed {
+            switch.active = !switch.active;
+        }
+        // don't trigger change detection each frame
+        if switch.pressed != pressed {
+            switch.pressed = pressed;
         }
     }
 }
 
+fn active_channels(switches: &Query<&ConveyorSwitch>) -> HashSet<u8> {
+    switches
+        .iter()
+        .filter(|switch| switch.active)
+        .map(|switch| switch.channel)
+        .collect()
+}
+
+/// Bodies which are moved by belts directly, not via [`MovementController`]
+type BeltBodies<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        &'static RigidBody,
+        &'static mut Velocity,
+    ),
+    (Without<MovementController>, Without<Dead>),
+>;
+
+/// Belts drive velocity of dynamic bodies, and act as moving ground for [`MovementController`]
 fn belt_move(
-    colliding: Query<(&CollidingEntities, &Transform), With<Belt>>,
-    mut entities: Query<&mut Transform, Without<Belt>>,
+    belts: Query<(&CollidingEntities, &Transform, &Belt)>,
+    switches: Query<&ConveyorSwitch>,
+    mut controllers: Query<(&GlobalTransform, &mut MovementController)>,
+    mut bodies: BeltBodies,
     time: Res<Time>,
 ) {
-    let speed = 3. * time.delta_seconds();
-    let side_speed = speed * 0.5;
+    let _scope = ProfileScope::new("belt_move");
+
+    let k_velocity = 8.;
 
     let mut affected: HashSet<_> = default();
 
-    for (colliding, transform) in colliding.iter() {
-        if !colliding.is_empty() {
-            let conv_angle = transform.rotation.to_euler(EulerRot::ZYX).0 - FRAC_PI_2;
-            let forward = rotate_vec2(Vec2::X, conv_angle) * speed;
-
-            let is_vertical = forward.y.abs() > forward.x.abs();
-            let conv_pos = transform.translation;
-
-            for entity in colliding.iter() {
-                if let Ok(mut transform) = entities.get_mut(entity) {
-                    if affected.insert(entity) {
-                        transform.translation.x += forward.x;
-                        transform.translation.y += forward.y;
-
-                        match is_vertical {
-                            true => {
-                                let delta = transform.translation.x - conv_pos.x;
-                                if delta.abs() > 0.1 {
-                                    transform.translation.x -= side_speed.copysign(delta);
-                                }
-                            }
-                            false => {
-                                let delta = transform.translation.y - conv_pos.y;
-                                if delta.abs() > 0.1 {
-                                    transform.translation.y -= side_speed.copysign(delta);
-                                }
-                            }
-                        }
-                    }
+    let active_channels = active_channels(&switches);
+
+    for (colliding, transform, belt) in belts.iter() {
+        if colliding.is_empty() {
+            continue;
+        }
+
+        let conv_angle = transform.rotation.to_euler(EulerRot::ZYX).0 - FRAC_PI_2;
+        let forward = rotate_vec2(Vec2::X, conv_angle);
+
+        let switched = belt
+            .channel
+            .map(|channel| active_channels.contains(&channel))
+            .unwrap_or(false);
+        let Some(direction) = (match switched {
+            true => belt.switched.direction(forward),
+            false => Some(forward),
+        }) else {
+            continue;
+        };
+
+        let conv_pos = transform.translation.truncate();
+
+        let side_speed = belt.speed * 0.5;
+
+        // keep objects in the middle of the belt
+        let belt_velocity = |pos: Vec2| {
+            let side = direction.perp();
+            let delta = (pos - conv_pos).dot(side);
+            let side_velocity = match delta.abs() > 0.1 {
+                true => -side_speed.copysign(delta),
+                false => 0.,
+            };
+            direction * belt.speed + side * side_velocity
+        };
+
+        for entity in colliding.iter() {
+            if !affected.insert(entity) {
+                continue;
+            }
+
+            if let Ok((pos, mut controller)) = controllers.get_mut(entity) {
+                controller.ground_velocity = belt_velocity(pos.translation().truncate());
+            } else if let Ok((pos, body, mut velocity)) = bodies.get_mut(entity) {
+                if *body == RigidBody::Dynamic {
+                    let target = belt_velocity(pos.translation().truncate());
+                    let t = (k_velocity * time.delta_seconds()).min(1.);
+                    velocity.linvel = velocity.linvel.lerp(target, t);
                 }
             }
         }
@@ -167,32 +520,62 @@ fn chute_spawn(
     mut spawners: Query<(&Transform, &mut ChuteSpawn)>,
     mut commands: Commands,
     time: Res<Time>,
+    switches: Query<&ConveyorSwitch>,
+    player: Query<&GlobalTransform, (With<Player>, Without<Dead>)>,
+    alive: Query<(), (With<GameObject>, Without<Dead>)>,
 ) {
+    let active_channels = active_channels(&switches);
+    let player_pos = player.get_single().ok().map(|pos| pos.translation().truncate());
+
     for (transform, mut spawner) in spawners.iter_mut() {
         let spawner = &mut *spawner;
+        let schedule = &spawner.schedule;
 
-        let mut check = |burst_cooldown: Duration, single_delay: Duration, burst_count: usize| {
-            let burst_count = burst_count.max(1);
+        let active = match schedule.trigger {
+            ChuteTrigger::Always => true,
+            ChuteTrigger::Switch(channel) => active_channels.contains(&channel),
+            ChuteTrigger::PlayerNear(distance) => player_pos
+                .map(|pos| pos.distance(transform.translation.truncate()) < distance)
+                .unwrap_or(false),
+        };
+        if !active {
+            continue;
+        }
 
-            if time.elapsed() >= spawner.burst_after {
-                spawner.burst_after =
-                    time.elapsed() + burst_cooldown + single_delay * (burst_count - 1) as u32;
-                spawner.burst_left = burst_count;
-                spawner.single_after = time.elapsed();
-            }
+        if !spawner.cooldown.tick(time.delta()).finished() {
+            continue;
+        }
 
-            if time.elapsed() >= spawner.single_after && spawner.burst_left != 0 {
-                spawner.burst_left -= 1;
-                if spawner.burst_left != 0 {
-                    spawner.single_after = spawner.single_after + single_delay;
-                }
-                true
-            } else {
-                false
+        spawner.spawned.retain(|entity| alive.contains(*entity));
+        if let Some(max_alive) = schedule.max_alive {
+            if spawner.spawned.len() >= max_alive as usize {
+                continue;
             }
+        }
+
+        // values from the file aren't validated; clamp passes NaN through
+        let seconds = |(min, max): (f32, f32)| {
+            let clamp = |value: f32| match value.is_finite() {
+                true => value.clamp(0., MAX_CHUTE_INTERVAL),
+                false => 0.,
+            };
+            Duration::from_secs_f32((clamp(min)..clamp(max)).random())
         };
 
-        let pos = || {
+        if spawner.burst_left == 0 {
+            let (min, max) = schedule.burst;
+            spawner.burst_left = (min..=max).random().max(1);
+        }
+        spawner.burst_left -= 1;
+
+        spawner.cooldown = Timer::once(match spawner.burst_left {
+            0 => seconds(schedule.burst_interval),
+            _ => seconds(schedule.spawn_interval),
+        });
+
+        let Some(object) = schedule.random_object() else { continue; };
+
+        let pos = {
             let angle = transform.rotation.to_euler(EulerRot::ZYX).0 - FRAC_PI_2;
             let forward = rotate_vec2(Vec2::X, angle);
 
@@ -200,40 +583,15 @@ fn chute_spawn(
             Transform::from_translation(pos.extend(0.))
         };
 
-        match spawner.output {
-            ConveyorOutput::BarrelsRareGroup => {
-                if check(Duration::from_millis(6000), Duration::from_millis(400), 4) {
-                    commands.spawn((GameObjectBundle::new("barrel", pos()), Barrel::Fire));
-                }
-            }
-            ConveyorOutput::BarrelsFrequent => {
-                if check(Duration::from_millis(2000), Duration::from_millis(800), 3) {
-                    commands.spawn((GameObjectBundle::new("barrel", pos()), Barrel::Fire));
-                }
-            }
-            ConveyorOutput::RandomEnemies => {
-                // if check(Duration::from_millis((5000..12000).random()), default(), 1) {
-                //     commands.spawn((GameObjectBundle::new("enemy_spam", pos()), Enemy::Spam));
-                // }
-            }
-            ConveyorOutput::Random => {
-                if check(
-                    Duration::from_millis((2000..6000).random()),
-                    Duration::from_millis((4000..20000).random()),
-                    (1..4).random(),
-                ) {
-                    match (0. ..1.).random() < 0.1 {
-                        true => {
-                            // commands
-                            //     .spawn((GameObjectBundle::new("enemy_spam", pos()), Enemy::Spam));
-                        }
-                        false => {
-                            commands.spawn((GameObjectBundle::new("barrel", pos()), Barrel::Fire));
-                        }
-                    };
-                }
-            }
-        }
+        let entity = match object {
+            ChuteObject::Barrel(barrel) => commands
+                .spawn((GameObjectBundle::new("barrel", pos), barrel))
+                .id(),
+            ChuteObject::Enemy(enemy) => commands
+                .spawn((GameObjectBundle::new("enemy", pos), enemy))
+                .id(),
+        };
+        spawner.spawned.push(entity);
     }
 }
 
@@ -1,29 +1,94 @@
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
+use crate::app::console::ConsoleOutput;
 use crate::app::scheduling::SpawnSet;
-use crate::gameplay::balance::OVERLOAD_ENEMY_REGULAR;
+use crate::gameplay::balance::Balance;
+use crate::gameplay::master::level::spawn::GameObjectBundle;
 use crate::gameplay::master::level_progress::ImportantEnemy;
 use crate::gameplay::mechanics::ai::*;
 use crate::gameplay::mechanics::damage::DamageType;
 use crate::gameplay::mechanics::damage::Projectile;
 use crate::gameplay::mechanics::overload::Overload;
+use crate::gameplay::mechanics::status::StatusEffect;
+use crate::gameplay::mechanics::status::StatusEffects;
 use crate::gameplay::physics::*;
 use crate::gameplay::utils::RotateToTarget;
 use crate::utils::bevy::commands::FallibleCommands;
+use crate::utils::misc_utils::serde_duration_secs;
 use crate::utils::random::RandomVec;
 use bevy::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 use std::time::Duration;
 
-#[derive(Component)]
+#[derive(
+    Component, Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
 pub enum Enemy {
     Important,
-    #[allow(unused)]
+    /// Dynamic body, so it can be moved by conveyors
     Spam,
 }
 
+impl Enemy {
+    pub fn all() -> impl Iterator<Item = Self> {
+        [Self::Important, Self::Spam].into_iter()
+    }
+}
+
+/// Per-enemy values, stored in [`Balance`]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnemyStats {
+    /// Overload threshold
+    pub overload: f32,
+    #[serde(with = "serde_duration_secs")]
+    pub shoot_period: Duration,
+    pub projectile_damage: u32,
+    pub projectile_speed: f32,
+    pub projectile_radius: f32,
+}
+
+impl Default for EnemyStats {
+    fn default() -> Self {
+        Self {
+            overload: 5.,
+            shoot_period: Duration::from_millis(80),
+            projectile_damage: 1,
+            projectile_speed: 6.,
+            projectile_radius: 0.15,
+        }
+    }
+}
+
+impl EnemyStats {
+    fn shoot(&self, balance: &Balance) -> Shoot {
+        Shoot {
+            period: self.shoot_period,
+            projectile: Projectile {
+                damage: self.projectile_damage,
+                speed: self.projectile_speed,
+                radius: self.projectile_radius,
+                ty: DamageType::Player,
+                status: Some(StatusEffect::slowed(balance)),
+            },
+        }
+    }
+}
+
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostUpdate, spawn_player.in_set(SpawnSet::Roots));
+        app.add_console_command("enemy", "[Important|Spam]", "Spawn enemy at cursor")
+            .add_systems(PostUpdate, spawn_player.in_set(SpawnSet::Roots))
+            .add_systems(
+                Update,
+                (
+                    update_balance.run_if(resource_changed::<Balance>()),
+                    console_command.run_if(on_event::<ConsoleCommand>()),
+                ),
+            );
     }
 }
 
@@ -32,42 +97,94 @@ const ENEMY_RADIUS: f32 = 0.7;
 fn spawn_player(
     mut new: Query<(Entity, &mut Transform, &Enemy), Added<Enemy>>,
     mut commands: Commands,
+    balance: Res<Balance>,
 ) {
     for (entity, mut transform, enemy) in new.iter_mut() {
+        let stats = balance.enemy(*enemy);
+
         let target_dir = Vec2::random_dir();
         transform.rotation = Quat::from_rotation_z(-target_dir.angle_between(Vec2::X));
 
         commands.try_insert(
             entity,
             (
-                TypicalBody {
-                    body: RigidBody::Fixed,
-                    ..TypicalBody::new_ball(ENEMY_RADIUS)
-                        .friction(0.3)
-                        .restitution(0.)
-                        .mass(120.)
-                },
                 PhysicsType::Enemy.groups(),
                 RotateToTarget::new_from_time(0.5).with_target(target_dir),
                 //
                 Target::default(),
-                Shoot {
-                    period: Duration::from_millis(80),
-                    projectile: Projectile {
-                        damage: 1,
-                        speed: 6.,
-                        radius: 0.15,
-                        ty: DamageType::Player,
-                    },
-                },
+                stats.shoot(&balance),
                 //
-                Overload::new(OVERLOAD_ENEMY_REGULAR),
+                Overload::new(stats.overload),
+                StatusEffects::default(),
             ),
         );
 
+        let body = TypicalBody::new_ball(ENEMY_RADIUS)
+            .friction(0.3)
+            .restitution(0.)
+            .mass(120.);
+
         match enemy {
-            Enemy::Important => commands.try_insert(entity, ImportantEnemy),
-            Enemy::Spam => (),
+            Enemy::Important => commands.try_insert(
+                entity,
+                (
+                    TypicalBody {
+                        body: RigidBody::Fixed,
+                        ..body
+                    },
+                    ImportantEnemy,
+                ),
+            ),
+            Enemy::Spam => commands.try_insert(
+                entity,
+                (
+                    body.lock_rotation(),
+                    Damping {
+                        linear_damping: 2.,
+                        angular_damping: 0.,
+                    },
+                    Velocity::default(),
+                ),
+            ),
         }
     }
 }
+
+/// Apply changed stats to live enemies
+fn update_balance(mut enemies: Query<(&Enemy, &mut Shoot, &mut Overload)>, balance: Res<Balance>) {
+    for (enemy, mut shoot, mut overload) in enemies.iter_mut() {
+        let stats = balance.enemy(*enemy);
+        *shoot = stats.shoot(&balance);
+        overload.max = stats.overload;
+    }
+}
+
+fn console_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut entity_commands: Commands,
+) {
+    for command in commands.read().filter(|command| command.is("enemy")) {
+        let Some(pos) = command.cursor else {
+            output.send(ConsoleOutput::error("cursor is outside of the world"));
+            continue;
+        };
+
+        let enemy = match command.args.first().map(|arg| arg.to_lowercase()) {
+            None => Enemy::Spam,
+            Some(arg) => match Enemy::all().find(|enemy| format!("{enemy:?}").to_lowercase() == arg) {
+                Some(enemy) => enemy,
+                None => {
+                    output.send(ConsoleOutput::error(format!("no such enemy \"{arg}\"")));
+                    continue;
+                }
+            },
+        };
+
+        entity_commands.spawn((
+            GameObjectBundle::new("enemy", Transform::from_translation(pos.extend(0.))),
+            enemy,
+        ));
+        output.send(ConsoleOutput::info(format!("spawned {enemy:?} at {pos}")));
+    }
+}
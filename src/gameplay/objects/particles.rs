@@ -1,29 +1,34 @@
 use super::barrels::Barrel;
 use super::barrels::Explosion;
 use super::barrels::OnFire;
-use crate::gameplay::balance::*;
+use crate::gameplay::balance::Balance;
 use crate::gameplay::master::level::spawn::GameObjectBundle;
-use crate::gameplay::mechanics::damage::ApplyDamage;
 use crate::gameplay::mechanics::damage::DamageType;
-use crate::gameplay::mechanics::damage::Dead;
-use crate::gameplay::mechanics::damage::Health;
 use crate::gameplay::mechanics::damage::Projectile;
 use crate::gameplay::mechanics::damage::ProjectileImpact;
-use crate::gameplay::mechanics::overload::Overload;
 use crate::gameplay::mechanics::overload::OverloadSource;
+use crate::gameplay::mechanics::overload::Overloaded;
+use crate::gameplay::mechanics::status::StatusChanged;
+use crate::gameplay::mechanics::status::StatusEffects;
+use crate::gameplay::mechanics::status::StatusKind;
 use crate::gameplay::mechanics::MechanicSet;
 use crate::gameplay::physics::*;
 use crate::gameplay::utils::Lifetime;
 use crate::utils::bevy::commands::FallibleCommands;
 use crate::utils::math_algorithms::dir_vec2;
+use crate::utils::misc_utils::serde_duration_secs;
 use crate::utils::misc_utils::ExtendedTime;
 use crate::utils::random::RandomRange;
 use crate::utils::random::RandomVec;
 use bevy::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 use std::f32::consts::TAU;
 use std::time::Duration;
 
-#[derive(Component, Clone, Copy)]
+#[derive(
+    Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize,
+)]
 pub enum Particle {
     ProjectileImpact,
     FireImpact,
@@ -32,27 +37,44 @@ pub enum Particle {
     OverloadedSparks,
 }
 
+/// Stored in [`Balance`]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ParticleDescriptor {
-    size: f32,
+    pub size: f32,
     pub graphical_size: f32,
-    distance: f32,
-    graphical_count: usize,
-    overload_power: f32,
-    overload_lifetime: Option<Duration>,
+    pub distance: f32,
+    pub graphical_count: usize,
+    /// Overload source is spawned only if this is not zero
+    pub overload_power: f32,
+    #[serde(with = "serde_duration_secs")]
+    pub overload_lifetime: Duration,
+    #[serde(with = "serde_duration_secs")]
     pub lifetime: Duration,
     pub z_offset: f32,
 }
 
 impl Particle {
-    pub fn descriptor(self) -> ParticleDescriptor {
+    pub fn all() -> impl Iterator<Item = Self> {
+        [
+            Self::ProjectileImpact,
+            Self::FireImpact,
+            Self::ColdFire,
+            Self::Shockwave,
+            Self::OverloadedSparks,
+        ]
+        .into_iter()
+    }
+
+    /// Used if not specified in balance file
+    pub fn default_descriptor(self) -> ParticleDescriptor {
         match self {
             Particle::ProjectileImpact => ParticleDescriptor {
                 size: 0.1,
                 graphical_size: 0.7,
                 distance: 1.,
                 graphical_count: 8,
-                overload_power: OVERLOAD_PROJECTILE_IMPACT,
-                overload_lifetime: Some(OVERLOAD_DURATION_PARTICLE),
+                overload_power: 0.03,
+                overload_lifetime: Duration::from_millis(1000),
                 lifetime: Duration::from_millis(1200),
                 z_offset: 1.,
             },
@@ -62,8 +84,8 @@ impl Particle {
                 graphical_size: 0.5,
                 distance: 0.5,
                 graphical_count: 4,
-                overload_power: OVERLOAD_FIRE_IMPACT,
-                overload_lifetime: Some(OVERLOAD_DURATION_PARTICLE),
+                overload_power: 0.05,
+                overload_lifetime: Duration::from_millis(1000),
                 lifetime: Duration::from_millis(600),
                 z_offset: 1.2,
             },
@@ -74,7 +96,7 @@ impl Particle {
                 distance: 1.,
                 graphical_count: 8,
                 overload_power: 0.,
-                overload_lifetime: None,
+                overload_lifetime: Duration::ZERO,
                 lifetime: Duration::from_millis(800),
                 z_offset: 1.2,
             },
@@ -85,7 +107,7 @@ impl Particle {
                 distance: 1.,
                 graphical_count: 1,
                 overload_power: 0.,
-                overload_lifetime: None,
+                overload_lifetime: Duration::ZERO,
                 lifetime: Duration::from_millis(300),
                 z_offset: 0.6,
             },
@@ -96,15 +118,15 @@ impl Particle {
                 distance: 1.,
                 graphical_count: 4,
                 overload_power: 0.,
-                overload_lifetime: None,
+                overload_lifetime: Duration::ZERO,
                 lifetime: Duration::from_millis(300),
                 z_offset: 2.,
             },
         }
     }
 
-    fn graphical_bundle(self, pos: Vec2, end_delta: Vec2) -> impl Bundle {
-        let descr = self.descriptor();
+    fn graphical_bundle(self, balance: &Balance, pos: Vec2, end_delta: Vec2) -> impl Bundle {
+        let descr = balance.particle(self);
         (
             GameObjectBundle::new("projectile", Transform::from_translation(pos.extend(0.))),
             Lifetime(descr.lifetime),
@@ -122,9 +144,9 @@ impl Particle {
         )
     }
 
-    fn overload_bundle(self, pos: Vec2) -> impl Bundle {
-        let descr = self.descriptor();
-        let lifetime = descr.overload_lifetime.unwrap_or(descr.lifetime);
+    fn overload_bundle(self, balance: &Balance, pos: Vec2) -> impl Bundle {
+        let descr = balance.particle(self);
+        let lifetime = descr.overload_lifetime;
         (
             GameObjectBundle::new(
                 "projectile overload",
@@ -146,33 +168,50 @@ pub struct ParticlesPlugin;
 impl Plugin for ParticlesPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            (particle_events, on_fire, on_explosion, on_overload).after(MechanicSet::PostReaction),
+            FixedUpdate,
+            (
+                particle_events,
+                on_fire,
+                on_explosion,
+                on_overload,
+                on_status,
+            )
+                .after(MechanicSet::PostReaction),
         );
     }
 }
 
-pub fn spawn_particle(commands: &mut Commands, pos: Vec2, ty: Particle, overload: bool) {
-    let descr = ty.descriptor();
+pub fn spawn_particle(
+    commands: &mut Commands,
+    balance: &Balance,
+    pos: Vec2,
+    ty: Particle,
+    overload: bool,
+) {
+    let descr = balance.particle(ty);
 
     for _ in 0..descr.graphical_count {
         let dir = Vec2::random_dir() * (descr.distance * 0.5..descr.distance * 1.5).random();
-        commands.spawn(ty.graphical_bundle(pos, dir));
+        commands.spawn(ty.graphical_bundle(balance, pos, dir));
     }
 
     if descr.overload_power > 0. && overload {
-        commands.spawn(ty.overload_bundle(pos));
+        commands.spawn(ty.overload_bundle(balance, pos));
     }
 }
 
-fn particle_events(mut projectile_impact: EventReader<ProjectileImpact>, mut commands: Commands) {
+fn particle_events(
+    mut projectile_impact: EventReader<ProjectileImpact>,
+    mut commands: Commands,
+    balance: Res<Balance>,
+) {
     for ProjectileImpact {
         pos,
         projectile,
         hit,
     } in projectile_impact.read().copied()
     {
-        let mut spawn = |ty| spawn_particle(&mut commands, pos, ty, hit);
+        let mut spawn = |ty| spawn_particle(&mut commands, &balance, pos, ty, hit);
 
         match projectile.ty {
             DamageType::Player => spawn(Particle::ProjectileImpact),
@@ -188,6 +227,7 @@ fn on_fire(
     barrels: Query<(&GlobalTransform, &Velocity, &OnFire)>,
     mut commands: Commands,
     time: Res<Time>,
+    balance: Res<Balance>,
 ) {
     let period = Duration::from_millis(500);
 
@@ -197,31 +237,75 @@ fn on_fire(
                 let pos = pos.translation().truncate();
                 let delta = velocity.linvel + Vec2::random_dir() * (0.3..1.).random();
 
-                commands.spawn((Particle::FireImpact.graphical_bundle(pos, delta),));
-                commands.spawn((Particle::ColdFire.graphical_bundle(pos, delta * 0.7),));
+                commands.spawn((Particle::FireImpact.graphical_bundle(&balance, pos, delta),));
+                commands.spawn((Particle::ColdFire.graphical_bundle(&balance, pos, delta * 0.7),));
             }
         }
     }
 }
 
+/// Graphics only
+fn on_status(
+    entities: Query<(&GlobalTransform, Option<&Velocity>, &StatusEffects)>,
+    mut changed: EventReader<StatusChanged>,
+    mut commands: Commands,
+    time: Res<Time>,
+    balance: Res<Balance>,
+) {
+    let period = Duration::from_millis(300);
+
+    let particle = |kind| match kind {
+        StatusKind::Burning => Particle::FireImpact,
+        StatusKind::Stunned => Particle::OverloadedSparks,
+        StatusKind::Slowed => Particle::ColdFire,
+    };
+
+    // burst when effect starts
+    for event in changed.read().filter(|event| event.active) {
+        if let Ok((pos, _, _)) = entities.get(event.entity) {
+            spawn_particle(
+                &mut commands,
+                &balance,
+                pos.translation().truncate(),
+                particle(event.kind),
+                false,
+            );
+        }
+    }
+
+    if !time.is_tick(period, default()) {
+        return;
+    }
+
+    for (pos, velocity, status) in entities.iter() {
+        for kind in StatusKind::all().filter(|kind| status.has(*kind)) {
+            let pos = pos.translation().truncate();
+            let velocity = velocity.map(|v| v.linvel).unwrap_or_default();
+
+            for _ in 0..status.stacks(kind) {
+                let delta = velocity + Vec2::random_dir() * (0.3..1.).random();
+                commands.spawn(particle(kind).graphical_bundle(&balance, pos, delta));
+            }
+        }
+    }
+}
+
+/// Graphics, fireballs and overload. Physical effects are in [`super::barrels`].
 fn on_explosion(
     mut explosions: EventReader<Explosion>,
     mut commands: Commands,
-    mut damage: EventWriter<ApplyDamage>,
-    physics: Res<RapierContext>,
-    victims: Query<&GlobalTransform, With<Health>>,
+    balance: Res<Balance>,
 ) {
-    let shockwave_distance = 3.;
-    let shockwave_shape = Collider::ball(shockwave_distance - 0.4);
+    let shockwave_distance = balance.explosion_radius;
 
     for Explosion { at, ty } in explosions.read() {
         let pos = at.truncate();
         let dir = |distance: f32| Vec2::random_dir() * (distance * 0.5..distance * 1.5).random();
 
         let mut spawn = |ty: Particle| {
-            let descr = ty.descriptor();
+            let descr = balance.particle(ty);
             for _ in 0..descr.graphical_count {
-                commands.spawn(ty.graphical_bundle(pos, dir(descr.distance)));
+                commands.spawn(ty.graphical_bundle(&balance, pos, dir(descr.distance)));
             }
         };
 
@@ -238,7 +322,11 @@ fn on_explosion(
                     let angle = ad * index as f32 + (-ad..ad).random() * 0.1;
 
                     let ty = Particle::Shockwave;
-                    commands.spawn(ty.graphical_bundle(pos, dir_vec2(angle) * shockwave_distance));
+                    commands.spawn(ty.graphical_bundle(
+                        &balance,
+                        pos,
+                        dir_vec2(angle) * shockwave_distance,
+                    ));
                 }
 
                 // fireballs
@@ -248,60 +336,26 @@ fn on_explosion(
                     commands.spawn(
                         Projectile {
                             damage: 1,
-                            speed: SPEED_FIREBALL_EXPLOSION,
+                            speed: balance.explosion_fireball_speed,
                             radius: 0.5,
                             ty: DamageType::Barrels,
+                            status: None,
                         }
-                        .bundle(pos + direction * 0.5, direction),
+                        .bundle(&balance, pos + direction * 0.5, direction),
                     );
                 }
 
                 // overload
                 commands.spawn((
                     GameObjectBundle::new("explosion", Transform::from_translation(pos.extend(0.))),
-                    Lifetime(OVERLOAD_DURATION_EXPLOSION),
+                    Lifetime(balance.overload_duration_explosion),
                     //
                     Collider::ball(1.),
                     PhysicsType::Overload.groups(),
                     OverloadSource {
-                        power: OVERLOAD_EXPLOSION,
+                        power: balance.overload_explosion,
                     },
                 ));
-
-                // apply damage
-                physics.intersections_with_shape(
-                    pos,
-                    0.,
-                    &shockwave_shape,
-                    PhysicsType::Object.filter(),
-                    |victim| {
-                        if let Ok(transform) = victims.get(victim) {
-                            damage.send(ApplyDamage {
-                                victim,
-                                amount: 3,
-                                ty: DamageType::Barrels,
-                            });
-                            damage.send(ApplyDamage {
-                                victim,
-                                amount: 2,
-                                ty: DamageType::Player,
-                            });
-                            commands.try_insert(
-                                victim,
-                                ExternalImpulse {
-                                    impulse: {
-                                        let target = transform.translation().truncate();
-                                        let delta = target - pos;
-                                        let delta = delta.normalize_or_zero();
-                                        delta * 500.
-                                    },
-                                    ..default()
-                                },
-                            );
-                        }
-                        true
-                    },
-                );
             }
         }
     }
@@ -311,28 +365,31 @@ fn on_explosion(
 struct OverloadedSince(Duration);
 
 fn on_overload(
-    new: Query<(Entity, &GlobalTransform), (With<Overload>, Added<Dead>)>,
+    mut new: EventReader<Overloaded>,
+    positions: Query<&GlobalTransform>,
     overloaded: Query<(Entity, &OverloadedSince, &GlobalTransform)>,
     mut commands: Commands,
     time: Res<Time>,
+    balance: Res<Balance>,
 ) {
     let period_1 = Duration::from_millis(30);
     let duration_1 = Duration::from_millis(1500);
     let period_2 = Duration::from_millis(300);
     let duration_2 = Duration::from_millis(8000);
 
-    for (entity, pos) in new.iter() {
+    for &Overloaded { entity } in new.read() {
+        let Ok(pos) = positions.get(entity) else { continue; };
         commands.try_insert(entity, OverloadedSince(time.elapsed()));
 
         let pos = pos.translation().truncate();
         commands.spawn((
             GameObjectBundle::new("overloaded", Transform::from_translation(pos.extend(0.))),
-            Lifetime(OVERLOAD_DURATION_OVERLOADED),
+            Lifetime(balance.overload_duration_overloaded),
             //
             Collider::ball(1.),
             PhysicsType::Overload.groups(),
             OverloadSource {
-                power: OVERLOAD_OVERLOADED,
+                power: balance.overload_overloaded,
             },
         ));
     }
@@ -351,7 +408,7 @@ fn on_overload(
 
         if time.is_tick(period, since.0) {
             let pos = pos.translation().truncate();
-            spawn_particle(&mut commands, pos, Particle::OverloadedSparks, true);
+            spawn_particle(&mut commands, &balance, pos, Particle::OverloadedSparks, true);
         }
     }
 }
@@ -1,12 +1,26 @@
+use super::barrels::Barrel;
 use super::elevators::Elevator;
 use super::particles::spawn_particle;
 use super::particles::Particle;
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
+use crate::app::console::ConsoleOutput;
 use crate::app::scheduling::SpawnSet;
-use crate::gameplay::balance::SPEED_FIREBALL_PLAYER;
+use crate::app::scores::Scores;
+use crate::gameplay::balance::Balance;
+use crate::gameplay::mechanics::abilities::update_abilities;
+use crate::gameplay::mechanics::abilities::Abilities;
+use crate::gameplay::mechanics::abilities::Ability;
+use crate::gameplay::mechanics::abilities::Energy;
+use crate::gameplay::mechanics::damage::ApplyDamage;
 use crate::gameplay::mechanics::damage::DamageType;
+use crate::gameplay::mechanics::damage::Dead;
+use crate::gameplay::mechanics::damage::GodMode;
 use crate::gameplay::mechanics::damage::Health;
+use crate::gameplay::mechanics::damage::Invulnerable;
 use crate::gameplay::mechanics::damage::Projectile;
 use crate::gameplay::mechanics::movement::MovementController;
+use crate::gameplay::mechanics::status::StatusEffects;
 use crate::gameplay::mechanics::MechanicSet;
 use crate::gameplay::physics::*;
 use crate::gameplay::utils::RotateToTarget;
@@ -30,17 +44,34 @@ pub struct Player {
     pub input_kick: bool,
     pub input_locked: Timer,
     pub kick_animation: bool,
-    kick_cooldown: Timer,
     kick_hit: Option<Timer>,
 
     pub input_fire: bool,
     pub input_pull: bool,
 
-    fire_cooldown: Timer,
     fire_count: usize,
 
     pull_active: bool,
     pull_cooldown: Option<Timer>,
+
+    pub input_dash: bool,
+    pub dash_animation: bool,
+    /// Timer and direction
+    dash: Option<(Timer, Vec2)>,
+
+    pub input_grab: bool,
+    /// World position of the cursor
+    pub input_aim: Option<Vec2>,
+    grabbed: Option<Grabbed>,
+}
+
+/// Object held by the player
+struct Grabbed {
+    entity: Entity,
+    /// Grab input was released after grabbing
+    released: bool,
+    /// How long throw is being charged
+    charge: Option<Duration>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
@@ -61,21 +92,29 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<PlayerEvent>()
+            .add_console_command("god", "", "Toggle player invulnerability")
             .add_systems(PostUpdate, spawn_player.in_set(SpawnSet::Roots))
+            .add_systems(Update, console_command.run_if(on_event::<ConsoleCommand>()))
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
                     (update_player_state, on_collisions).after(MechanicSet::Reaction),
                     (fire_input, update_pull, kick).in_set(MechanicSet::Action),
+                    dash.in_set(MechanicSet::Input).after(update_abilities),
+                    grab_and_throw.in_set(MechanicSet::Action),
                 ),
             );
     }
 }
 
-pub const PLAYER_HEALTH: u32 = 30;
 const PLAYER_RADIUS: f32 = 0.2;
 
-fn spawn_player(new: Query<Entity, Added<Player>>, mut commands: Commands) {
+fn spawn_player(
+    new: Query<Entity, Added<Player>>,
+    mut commands: Commands,
+    scores: Res<Scores>,
+    balance: Res<Balance>,
+) {
     for entity in new.iter() {
         commands.try_insert(
             entity,
@@ -94,9 +133,12 @@ fn spawn_player(new: Query<Entity, Added<Player>>, mut commands: Commands) {
                 .bundle(),
                 RotateToTarget::new_from_time(0.35),
                 Health {
-                    value: PLAYER_HEALTH,
+                    value: balance.player_health,
                     ty: DamageType::Player,
                 },
+                Abilities::new(&scores.upgrades),
+                Energy::new(&balance),
+                StatusEffects::default(),
                 //
                 CollidingEntities::default(),
                 ActiveEvents::COLLISION_EVENTS,
@@ -132,22 +174,20 @@ fn on_collisions(
 }
 
 fn fire_input(
-    mut player: Query<(&GlobalTransform, &mut Player)>,
-    time: Res<Time>,
+    mut player: Query<(&GlobalTransform, &mut Player, &mut Abilities, &mut Energy)>,
     mut commands: Commands,
+    balance: Res<Balance>,
 ) {
-    for (pos, mut player) in player.iter_mut() {
+    for (pos, mut player, mut abilities, mut energy) in player.iter_mut() {
         let pos = pos.translation().truncate();
 
-        player.fire_cooldown.tick(time.delta());
-
-        if std::mem::take(&mut player.input_fire) && player.fire_cooldown.finished() {
-            player.fire_cooldown = Timer::once(Duration::from_millis(300));
-
+        if std::mem::take(&mut player.input_fire)
+            && abilities.try_use(Ability::Fire, &mut energy, &balance)
+        {
             let offset = (player.fire_count as f32 / 40.) % 1. * TAU;
             player.fire_count += 1;
 
-            let count = 8;
+            let count = 8 + 4 * abilities.level(Ability::Fire);
             for index in 0..count {
                 let da = TAU / count as f32;
                 let angle = index as f32 * da + offset + (-da..da).random();
@@ -158,15 +198,21 @@ fn fire_input(
                 commands.spawn(
                     Projectile {
                         damage: 1,
-                        speed: SPEED_FIREBALL_PLAYER,
+                        speed: balance.player_fireball_speed,
                         radius,
                         ty: DamageType::Barrels,
+                        status: None,
                     }
-                    .bundle(pos + direction * (PLAYER_RADIUS + radius + 0.5), direction),
+                    .bundle(
+                        &balance,
+                        pos + direction * (PLAYER_RADIUS + radius + 0.5),
+                        direction,
+                    ),
                 );
 
                 spawn_particle(
                     &mut commands,
+                    &balance,
                     pos + direction * (PLAYER_RADIUS + radius + 0.5),
                     Particle::FireImpact,
                     true,
@@ -177,19 +223,21 @@ fn fire_input(
 }
 
 fn update_pull(
-    mut player: Query<(&GlobalTransform, &mut Player)>,
+    mut player: Query<(&GlobalTransform, &mut Player, &mut Abilities, &mut Energy)>,
     time: Res<Time>,
     mut commands: Commands,
     objects: Query<&GlobalTransform, (Without<Player>, With<Collider>)>,
     physics: Res<RapierContext>,
+    balance: Res<Balance>,
 ) {
-    let max_distance = 6_f32;
     let scale_in = 0.35;
     let scale_out = 0.6;
     let impulse = 500.;
     let min_time = Duration::from_millis(400);
 
-    for (pos, mut player) in player.iter_mut() {
+    for (pos, mut player, mut abilities, mut energy) in player.iter_mut() {
+        let max_distance = 6. + 1.5 * abilities.level(Ability::Pull) as f32;
+
         // check if input is active
         if let Some(timer) = player.pull_cooldown.as_mut() {
             if timer.tick(time.delta()).finished() {
@@ -198,6 +246,10 @@ fn update_pull(
         }
         let input_pull = std::mem::take(&mut player.input_pull) || player.pull_cooldown.is_some();
 
+        // energy is spent only on activation
+        let input_pull = input_pull
+            && (player.pull_active || abilities.try_use(Ability::Pull, &mut energy, &balance));
+
         // decide what to do
         let scale = if player.pull_active && !input_pull {
             // disable pull (once)
@@ -276,24 +328,26 @@ fn update_pull(
 }
 
 fn kick(
-    mut player: Query<(&GlobalTransform, &mut Player)>,
+    mut player: Query<(&GlobalTransform, &mut Player, &mut Abilities, &mut Energy)>,
     objects: Query<&GlobalTransform, (Without<Player>, With<Collider>)>,
     physics: Res<RapierContext>,
     mut commands: Commands,
     time: Res<Time>,
+    mut damage: EventWriter<ApplyDamage>,
+    balance: Res<Balance>,
 ) {
-    let cooldown = Duration::from_millis(500);
     let hit_time = Duration::from_secs_f32(0.7 * 0.33); // sync to animation
-    let impulse = 800.;
     let distance = 0.8;
     let width = PLAYER_RADIUS * 2.;
 
-    for (transform, mut player) in player.iter_mut() {
-        player.kick_cooldown.tick(time.delta());
+    for (transform, mut player, mut abilities, mut energy) in player.iter_mut() {
+        let level = abilities.level(Ability::Kick);
+        let impulse = if level >= 2 { 1200. } else { 800. };
+        let ignites = level >= 1;
 
-        if std::mem::take(&mut player.input_kick) && player.kick_cooldown.finished() {
-            // player.input_locked = Timer::once(cooldown);
-            player.kick_cooldown = Timer::once(cooldown);
+        if std::mem::take(&mut player.input_kick)
+            && abilities.try_use(Ability::Kick, &mut energy, &balance)
+        {
             player.kick_hit = Some(Timer::once(hit_time));
             player.kick_animation = true;
         }
@@ -314,6 +368,14 @@ fn kick(
                     let dir = delta.normalize_or_zero();
                     let impulse = dir * impulse;
 
+                    if ignites {
+                        damage.send(ApplyDamage {
+                            victim: entity,
+                            amount: 1,
+                            ty: DamageType::Barrels,
+                        });
+                    }
+
                     commands.try_insert(
                         entity,
                         ExternalImpulse {
@@ -340,3 +402,199 @@ fn kick(
         }
     }
 }
+
+fn dash(
+    mut player: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut Player,
+        &mut Abilities,
+        &mut Energy,
+        &mut MovementController,
+    )>,
+    mut commands: Commands,
+    time: Res<Time>,
+    balance: Res<Balance>,
+) {
+    let duration = Duration::from_millis(200);
+    let speed = 16.;
+    let impulse = 400.;
+
+    for (entity, transform, mut player, mut abilities, mut energy, mut mvmt) in player.iter_mut() {
+        if std::mem::take(&mut player.input_dash)
+            && player.dash.is_none()
+            && abilities.try_use(Ability::Dash, &mut energy, &balance)
+        {
+            // dash in movement direction, or forward if standing still
+            let dir = mvmt.target_dir.try_normalize().unwrap_or_else(|| {
+                let angle = transform
+                    .compute_transform()
+                    .rotation
+                    .to_euler(EulerRot::ZYX)
+                    .0;
+                rotate_vec2(Vec2::X, angle)
+            });
+
+            let invulnerability = match abilities.level(Ability::Dash) {
+                0 => Duration::from_millis(300),
+                _ => Duration::from_millis(450),
+            };
+
+            player.dash = Some((Timer::once(duration), dir));
+            player.dash_animation = true;
+
+            commands.try_insert(
+                entity,
+                (
+                    ExternalImpulse {
+                        impulse: dir * impulse,
+                        ..default()
+                    },
+                    Invulnerable::new(DamageType::Player, invulnerability),
+                ),
+            );
+        }
+
+        if let Some((timer, dir)) = player.dash.as_mut() {
+            // keep controller from braking
+            mvmt.target_dir = *dir;
+            mvmt.speed_override = Some(speed);
+
+            if timer.tick(time.delta()).finished() {
+                player.dash = None;
+            }
+        }
+    }
+}
+
+fn grab_and_throw(
+    mut player: Query<(&GlobalTransform, &Velocity, &mut Player)>,
+    barrels: Query<&GlobalTransform, (With<Barrel>, Without<Dead>)>,
+    physics: Res<RapierContext>,
+    mut commands: Commands,
+    time: Res<Time>,
+) {
+    let grab_distance = 1.2;
+    let hold_distance = PLAYER_RADIUS + 0.45 + 0.3;
+    let max_hold_distance = hold_distance + 1.5;
+    let k_follow = 15.;
+    let max_charge = Duration::from_millis(1000);
+    let min_impulse = 400.;
+    let max_impulse = 1200.;
+
+    for (transform, velocity, mut player) in player.iter_mut() {
+        let input_grab = std::mem::take(&mut player.input_grab);
+
+        let transform = Transform::from(*transform);
+        let pos = transform.translation.truncate();
+        let angle = transform.rotation.to_euler(EulerRot::ZYX).0;
+        let forward = rotate_vec2(Vec2::X, angle);
+
+        let wall_between = |target: Vec2| {
+            physics
+                .cast_ray(pos, target - pos, 1., false, PhysicsType::WallOnly.filter())
+                .is_some()
+        };
+
+        // grab closest barrel in front
+        let Some(grabbed) = player.grabbed.as_mut() else {
+            if input_grab {
+                let mut closest: Option<(Entity, f32)> = None;
+
+                physics.intersections_with_shape(
+                    pos + forward * (grab_distance / 2.),
+                    angle,
+                    &Collider::cuboid(grab_distance / 2., hold_distance),
+                    PhysicsType::GravityPull.filter(),
+                    |entity| {
+                        if let Ok(target) = barrels.get(entity) {
+                            let target = target.translation().truncate();
+                            let distance = target.distance(pos);
+
+                            if !wall_between(target)
+                                && closest.map(|v| distance < v.1).unwrap_or(true)
+                            {
+                                closest = Some((entity, distance));
+                            }
+                        }
+                        true
+                    },
+                );
+
+                player.grabbed = closest.map(|(entity, _)| Grabbed {
+                    entity,
+                    released: false,
+                    charge: None,
+                });
+            }
+            continue;
+        };
+
+        // drop if barrel is gone, stuck or behind a wall
+        let Ok(target) = barrels.get(grabbed.entity) else {
+            player.grabbed = None;
+            continue;
+        };
+        let target = target.translation().truncate();
+
+        if target.distance(pos) > max_hold_distance || wall_between(target) {
+            player.grabbed = None;
+            continue;
+        }
+
+        // charge and throw
+        if !grabbed.released {
+            grabbed.released = !input_grab;
+        } else if input_grab {
+            let charge = grabbed.charge.get_or_insert(default());
+            *charge = (*charge + time.delta()).min(max_charge);
+        } else if let Some(charge) = grabbed.charge {
+            let entity = grabbed.entity;
+            player.grabbed = None;
+
+            let aim = player.input_aim.unwrap_or(target + forward);
+            let dir = (aim - target).try_normalize().unwrap_or(forward);
+            let t = charge.as_secs_f32() / max_charge.as_secs_f32();
+
+            commands.try_insert(
+                entity,
+                (
+                    Velocity::linear(velocity.linvel),
+                    ExternalImpulse {
+                        impulse: dir * lerp(min_impulse, max_impulse, t),
+                        ..default()
+                    },
+                ),
+            );
+            continue;
+        }
+
+        // follow point in front of the player
+        let hold_point = pos + forward * hold_distance;
+        let follow = (hold_point - target) * k_follow + velocity.linvel;
+        commands.try_insert(grabbed.entity, Velocity::linear(follow));
+    }
+}
+
+fn console_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    player: Query<(Entity, Has<GodMode>), With<Player>>,
+    mut entity_commands: Commands,
+) {
+    for _ in commands.read().filter(|command| command.is("god")) {
+        let Ok((entity, god_mode)) = player.get_single() else {
+            output.send(ConsoleOutput::error("no player"));
+            continue;
+        };
+
+        match god_mode {
+            true => entity_commands.try_remove::<GodMode>(entity),
+            false => entity_commands.try_insert(entity, GodMode),
+        }
+        output.send(ConsoleOutput::info(match god_mode {
+            true => "god mode disabled",
+            false => "god mode enabled",
+        }));
+    }
+}
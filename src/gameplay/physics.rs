@@ -1,7 +1,51 @@
+use crate::utils::bevy::commands::FallibleCommands;
+use crate::utils::plugins::profiler::ProfileScope;
 use bevy::prelude::*;
+use bevy::transform::TransformSystem;
 
 pub use bevy_rapier2d::prelude::*;
 
+/// Rate of [`FixedUpdate`], which runs both gameplay and physics
+pub const FIXED_TIMESTEP_HZ: f64 = 60.;
+
+/// Sets in [`FixedUpdate`] which keep rendered transforms of bodies interpolated between physics steps
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum InterpolationSet {
+    /// Before any gameplay: transforms are reset to the last simulated values,
+    /// and propagated, so [`GlobalTransform`] isn't interpolated either
+    Restore,
+    /// After physics: simulated values are saved
+    Record,
+}
+
+/// Interpolates transform of the body between two last fixed steps when rendering.
+///
+/// Added automatically to all non-fixed rigid bodies.
+/// Transform changed outside of the fixed steps is treated as a teleport.
+#[derive(Component)]
+pub struct FixedInterpolation {
+    previous: Transform,
+    current: Transform,
+    rendered: Transform,
+}
+
+impl FixedInterpolation {
+    fn new(transform: Transform) -> Self {
+        Self {
+            previous: transform,
+            current: transform,
+            rendered: transform,
+        }
+    }
+
+    /// Reset interpolation if transform was changed externally
+    fn check_teleport(&mut self, transform: &Transform) {
+        if *transform != self.rendered {
+            *self = Self::new(*transform);
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct TypicalBody {
     pub body: RigidBody,
@@ -53,8 +97,8 @@ impl TypicalBody {
     }
 }
 
-/// How bodies interact
-#[derive(Clone, Copy)]
+/// How bodies interact. Each type is a separate collision layer, see [`PhysicsType::COLLISIONS`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PhysicsType {
     /// Impenetrable by anything
     Wall,
@@ -75,48 +119,152 @@ pub enum PhysicsType {
 }
 
 impl PhysicsType {
-    pub fn groups(self) -> CollisionGroups {
-        let wall = Group::GROUP_1;
-        let object = Group::GROUP_2;
-        let enemy = Group::GROUP_3;
-        let projectile = Group::GROUP_4;
-        let wall_only = Group::GROUP_5;
-        let overload = Group::GROUP_6;
-        let gravity_pull = Group::GROUP_7;
-        let conveyor = Group::GROUP_8;
-
-        let (memberships, filters) = match self {
-            PhysicsType::Wall => (wall, Group::all()),
-            PhysicsType::WallOnly => (wall_only, wall),
-            PhysicsType::Object => (object, Group::all()),
-            PhysicsType::Enemy => (enemy, Group::all()),
-            PhysicsType::Projectile => (projectile, wall | object | gravity_pull),
-            PhysicsType::Overload => (overload, overload),
-            PhysicsType::GravityPull => (Group::all(), object | projectile),
-            // PhysicsType::Conveyor => (conveyor, object | enemy),
-            PhysicsType::Conveyor => (conveyor, object), // TODO: fix convetors
-        };
+    /// Collision matrix: each layer and all layers it collides with.
+    ///
+    /// Must be symmetric, this is checked by tests.
+    pub const COLLISIONS: &'static [(Self, &'static [Self])] = &[
+        (
+            Self::Wall,
+            &[
+                Self::Wall,
+                Self::WallOnly,
+                Self::Object,
+                Self::Enemy,
+                Self::Projectile,
+            ],
+        ),
+        (Self::WallOnly, &[Self::Wall]),
+        (
+            Self::Object,
+            &[
+                Self::Wall,
+                Self::Object,
+                Self::Enemy,
+                Self::Projectile,
+                Self::GravityPull,
+                Self::Conveyor,
+            ],
+        ),
+        (
+            Self::Enemy,
+            &[Self::Wall, Self::Object, Self::Enemy, Self::Conveyor],
+        ),
+        (
+            Self::Projectile,
+            &[Self::Wall, Self::Object, Self::GravityPull],
+        ),
+        (Self::Overload, &[Self::Overload]),
+        (Self::GravityPull, &[Self::Object, Self::Projectile]),
+        (Self::Conveyor, &[Self::Object, Self::Enemy]),
+    ];
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        [
+            Self::Wall,
+            Self::WallOnly,
+            Self::Object,
+            Self::Enemy,
+            Self::Projectile,
+            Self::Overload,
+            Self::GravityPull,
+            Self::Conveyor,
+        ]
+        .into_iter()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PhysicsType::Wall => "Wall",
+            PhysicsType::WallOnly => "Wall only",
+            PhysicsType::Object => "Object",
+            PhysicsType::Enemy => "Enemy",
+            PhysicsType::Projectile => "Projectile",
+            PhysicsType::Overload => "Overload",
+            PhysicsType::GravityPull => "Gravity pull",
+            PhysicsType::Conveyor => "Conveyor",
+        }
+    }
+
+    /// Layers this one collides with, as specified in [`Self::COLLISIONS`]
+    pub fn collides_with(self) -> &'static [Self] {
+        Self::COLLISIONS
+            .iter()
+            .find(|(ty, _)| *ty == self)
+            .map(|(_, with)| *with)
+            .unwrap_or_default()
+    }
+
+    /// Whether bodies of these types actually collide, i.e. both layers agree on that
+    pub fn collides(self, other: Self) -> bool {
+        let (a, b) = (self.groups(), other.groups());
+        a.memberships.intersects(b.filters) && b.memberships.intersects(a.filters)
+    }
 
+    pub fn groups(self) -> CollisionGroups {
         CollisionGroups {
-            memberships,
-            filters,
+            memberships: self.layer(),
+            filters: self
+                .collides_with()
+                .iter()
+                .fold(Group::NONE, |filters, ty| filters | ty.layer()),
         }
     }
 
     pub fn filter(self) -> QueryFilter<'static> {
         QueryFilter::new().groups(self.groups())
     }
+
+    fn layer(self) -> Group {
+        Group::from_bits_truncate(1 << self as u32)
+    }
 }
 
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule())
             .insert_resource(RapierConfiguration {
                 gravity: Vec2::ZERO,
+                timestep_mode: TimestepMode::Fixed {
+                    dt: 1. / FIXED_TIMESTEP_HZ as f32,
+                    substeps: 1,
+                },
                 ..default()
-            });
+            })
+            .insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ))
+            .init_resource::<StepScope>()
+            .configure_sets(
+                FixedUpdate,
+                (
+                    InterpolationSet::Restore.before(PhysicsSet::SyncBackend),
+                    InterpolationSet::Record.after(PhysicsSet::Writeback),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        init_interpolation,
+                        restore_simulated,
+                        bevy::transform::systems::sync_simple_transforms,
+                        bevy::transform::systems::propagate_transforms,
+                    )
+                        .chain()
+                        .in_set(InterpolationSet::Restore),
+                    record_simulated.in_set(InterpolationSet::Record),
+                    begin_step_scope
+                        .after(PhysicsSet::SyncBackend)
+                        .before(PhysicsSet::StepSimulation),
+                    end_step_scope
+                        .after(PhysicsSet::StepSimulation)
+                        .before(PhysicsSet::Writeback),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                interpolate_rendered.before(TransformSystem::TransformPropagate),
+            );
 
         #[cfg(feature = "dev_build")]
         app.add_plugins(RapierDebugRenderPlugin {
@@ -128,3 +276,118 @@ impl Plugin for PhysicsPlugin {
         });
     }
 }
+
+/// Measures duration of the physics step for the profiler
+#[derive(Resource, Default)]
+struct StepScope(Option<ProfileScope>);
+
+fn begin_step_scope(mut scope: ResMut<StepScope>) {
+    scope.0 = Some(ProfileScope::new("rapier step"));
+}
+
+fn end_step_scope(mut scope: ResMut<StepScope>) {
+    scope.0 = None;
+}
+
+fn init_interpolation(
+    new: Query<(Entity, &Transform, &RigidBody), Added<RigidBody>>,
+    mut commands: Commands,
+) {
+    for (entity, transform, body) in new.iter() {
+        if *body != RigidBody::Fixed {
+            commands.try_insert(entity, FixedInterpolation::new(*transform));
+        }
+    }
+}
+
+fn restore_simulated(mut bodies: Query<(&mut Transform, &mut FixedInterpolation)>) {
+    for (mut transform, mut interpolation) in bodies.iter_mut() {
+        interpolation.check_teleport(&transform);
+
+        // don't trigger change detection, or rapier will think body was moved
+        *transform.bypass_change_detection() = interpolation.current;
+        interpolation.rendered = interpolation.current;
+    }
+}
+
+fn record_simulated(mut bodies: Query<(&Transform, &mut FixedInterpolation)>) {
+    for (transform, mut interpolation) in bodies.iter_mut() {
+        interpolation.previous = interpolation.current;
+        interpolation.current = *transform;
+        interpolation.rendered = *transform;
+    }
+}
+
+fn interpolate_rendered(
+    mut bodies: Query<(&mut Transform, &mut FixedInterpolation)>,
+    time: Res<Time<Fixed>>,
+) {
+    let t = time.overstep_percentage();
+
+    for (mut transform, mut interpolation) in bodies.iter_mut() {
+        interpolation.check_teleport(&transform);
+
+        let previous = interpolation.previous;
+        let current = interpolation.current;
+        let rendered = Transform {
+            translation: previous.translation.lerp(current.translation, t),
+            rotation: previous.rotation.slerp(current.rotation, t),
+            scale: previous.scale.lerp(current.scale, t),
+        };
+
+        if *transform != rendered {
+            *transform = rendered;
+        }
+        interpolation.rendered = rendered;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PhysicsType;
+
+    #[test]
+    fn each_layer_specified_once() {
+        for ty in PhysicsType::all() {
+            let count = PhysicsType::COLLISIONS
+                .iter()
+                .filter(|(v, _)| *v == ty)
+                .count();
+            assert_eq!(count, 1, "{ty:?} must be specified exactly once");
+        }
+    }
+
+    #[test]
+    fn collision_matrix_is_symmetric() {
+        for ty in PhysicsType::all() {
+            for other in ty.collides_with() {
+                assert!(
+                    other.collides_with().contains(&ty),
+                    "{ty:?} collides with {other:?}, but not vice versa - they won't collide"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn expected_collisions() {
+        let expected = [
+            (PhysicsType::Projectile, PhysicsType::Object, true),
+            (PhysicsType::Projectile, PhysicsType::Enemy, false),
+            (PhysicsType::Projectile, PhysicsType::Projectile, false),
+            (PhysicsType::WallOnly, PhysicsType::Wall, true),
+            (PhysicsType::WallOnly, PhysicsType::Object, false),
+            (PhysicsType::Overload, PhysicsType::Overload, true),
+            (PhysicsType::Overload, PhysicsType::Wall, false),
+            (PhysicsType::GravityPull, PhysicsType::Projectile, true),
+            (PhysicsType::GravityPull, PhysicsType::Enemy, false),
+            (PhysicsType::Conveyor, PhysicsType::Enemy, true),
+            (PhysicsType::Conveyor, PhysicsType::Wall, false),
+        ];
+
+        for (a, b, collides) in expected {
+            assert_eq!(a.collides(b), collides, "{a:?} and {b:?}");
+            assert_eq!(b.collides(a), collides, "{b:?} and {a:?}");
+        }
+    }
+}
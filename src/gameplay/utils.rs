@@ -1,5 +1,6 @@
 use super::master::level::data::HALF_TILE;
 use super::master::level::data::TILE_SIZE;
+use super::mechanics::MechanicSet;
 use crate::utils::bevy::commands::FallibleCommands;
 use crate::utils::bevy::misc_utils::ExtendedTimer;
 use crate::utils::math_algorithms::lerp;
@@ -113,10 +114,8 @@ pub struct UtilsPlugin;
 
 impl Plugin for UtilsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (rotate_to_target, interpolate_transform_once, despawn_after),
-        );
+        app.add_systems(FixedUpdate, rotate_to_target.after(MechanicSet::Input))
+            .add_systems(Update, (interpolate_transform_once, despawn_after));
     }
 }
 
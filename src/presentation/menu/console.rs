@@ -0,0 +1,250 @@
+use crate::app::actions::AppActions;
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
+use crate::app::console::ConsoleCommands;
+use crate::app::console::ConsoleOutput;
+use crate::app::settings::AppSettings;
+use crate::utils::bevy_egui::*;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use egui::text::CCursor;
+use egui::text::CCursorRange;
+use leafwing_input_manager::prelude::ActionState;
+use std::collections::VecDeque;
+
+/// Developer console window. Only available in developer mode.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_console_command("clear", "", "Clear console output")
+            .add_systems(
+                Update,
+                (
+                    toggle_console,
+                    receive_output,
+                    draw_console.run_if(|console: Res<Console>| console.open),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Max number of output lines kept
+const MAX_LINES: usize = 500;
+
+/// Max number of commands kept in history
+const MAX_HISTORY: usize = 100;
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+
+    /// Entered commands, oldest first
+    history: Vec<String>,
+    /// Index in history while browsing it
+    history_pos: Option<usize>,
+
+    /// Text and color
+    lines: VecDeque<(String, Color)>,
+}
+
+impl Console {
+    fn print(&mut self, text: impl Into<String>, color: Color) {
+        if self.lines.len() >= MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back((text.into(), color));
+    }
+
+    fn add_history(&mut self, line: &str) {
+        self.history_pos = None;
+        if self.history.last().map(|last| last.as_str()) == Some(line) {
+            return;
+        }
+        if self.history.len() >= MAX_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(line.to_string());
+    }
+
+    /// Go back in history if `older`, forward otherwise
+    fn browse_history(&mut self, older: bool) {
+        self.history_pos = match (older, self.history_pos) {
+            (true, None) => self.history.len().checked_sub(1),
+            (true, Some(pos)) => Some(pos.saturating_sub(1)),
+            (false, Some(pos)) => Some(pos + 1).filter(|pos| *pos < self.history.len()),
+            (false, None) => None,
+        };
+        self.input = self
+            .history_pos
+            .map(|pos| self.history[pos].clone())
+            .unwrap_or_default();
+    }
+
+    /// Complete command name
+    fn autocomplete(&mut self, registry: &ConsoleCommands) {
+        if self.input.contains(char::is_whitespace) {
+            return;
+        }
+
+        let candidates: Vec<_> = registry.complete(&self.input).collect();
+        match candidates.as_slice() {
+            [] => (),
+            [name] => self.input = format!("{name} "),
+            [first, ..] => {
+                let common = candidates.iter().fold(first.len(), |len, name| {
+                    first
+                        .chars()
+                        .zip(name.chars())
+                        .take(len)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                self.input = first[..common].to_string();
+                self.print(candidates.join(" "), Color::GRAY);
+            }
+        }
+    }
+}
+
+fn toggle_console(
+    actions: Res<ActionState<AppActions>>,
+    settings: Res<AppSettings>,
+    mut console: ResMut<Console>,
+) {
+    if !settings.debug.developer_mode {
+        if console.open {
+            console.open = false;
+        }
+        return;
+    }
+
+    if actions.just_pressed(AppActions::Console) {
+        console.open = !console.open;
+    }
+}
+
+fn receive_output(mut output: EventReader<ConsoleOutput>, mut console: ResMut<Console>) {
+    for line in output.read() {
+        let color = match line.error {
+            true => Color::RED,
+            false => Color::WHITE,
+        };
+        console.print(line.text.clone(), color);
+    }
+}
+
+fn draw_console(
+    mut egui_ctx: EguiContexts,
+    mut console: ResMut<Console>,
+    registry: Res<ConsoleCommands>,
+    mut commands: EventWriter<ConsoleCommand>,
+    camera: Query<(&GlobalTransform, &Camera)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let console = &mut *console;
+
+    EguiPopup {
+        name: "draw_console",
+        anchor: egui::Align2::CENTER_TOP,
+        order: egui::Order::Foreground,
+        ..default()
+    }
+    .show(egui_ctx.ctx_mut(), |ui| {
+        let screen = ui.ctx().screen_rect();
+        ui.set_width(screen.width() * 0.8);
+
+        egui::ScrollArea::vertical()
+            .max_height(screen.height() * 0.4)
+            .stick_to_bottom(true)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for (text, color) in console.lines.iter() {
+                    ui.colored_label(color.to_egui(), text);
+                }
+            });
+
+        let output = egui::TextEdit::singleline(&mut console.input)
+            .hint_text("Type \"help\" for the list of commands")
+            .desired_width(f32::INFINITY)
+            .lock_focus(true)
+            .show(ui);
+
+        // focus is requested after the widget is shown, so key which opened console isn't typed
+        output.response.request_focus();
+
+        let (enter, escape, up, down, tab) = ui.input(|input| {
+            (
+                input.key_pressed(egui::Key::Enter),
+                input.key_pressed(egui::Key::Escape),
+                input.key_pressed(egui::Key::ArrowUp),
+                input.key_pressed(egui::Key::ArrowDown),
+                input.key_pressed(egui::Key::Tab),
+            )
+        });
+
+        if escape {
+            console.open = false;
+            return;
+        }
+
+        let input_before = console.input.clone();
+
+        if up || down {
+            console.browse_history(up);
+        }
+        if tab {
+            console.autocomplete(&registry);
+        }
+
+        if enter {
+            let line = std::mem::take(&mut console.input);
+            let line = line.trim();
+            if line.is_empty() {
+                return;
+            }
+
+            console.add_history(line);
+            console.print(format!("> {line}"), Color::GRAY);
+
+            let world_cursor = world_cursor(&camera, &window);
+            match ConsoleCommand::parse(line, world_cursor) {
+                Some(command) if command.is("clear") => console.lines.clear(),
+                Some(command) if registry.0.contains_key(command.name.as_str()) => {
+                    commands.send(command)
+                }
+                Some(command) => console.print(
+                    format!("unknown command \"{}\"", command.name),
+                    Color::RED,
+                ),
+                None => (),
+            }
+        }
+
+        // move text cursor to the end of replaced text
+        if console.input != input_before {
+            let mut state = output.state;
+            let end = CCursor::new(console.input.chars().count());
+            state.set_ccursor_range(Some(CCursorRange::one(end)));
+            state.store(ui.ctx(), output.response.id);
+        }
+    });
+}
+
+/// Cursor position on the ground plane, as seen by the topmost active camera
+pub(super) fn world_cursor(
+    camera: &Query<(&GlobalTransform, &Camera)>,
+    window: &Query<&Window, With<PrimaryWindow>>,
+) -> Option<Vec2> {
+    let cursor = window.get_single().ok()?.cursor_position()?;
+    let (camera_pos, camera) = camera
+        .iter()
+        .filter(|(_, camera)| camera.is_active)
+        .max_by_key(|(_, camera)| camera.order)?;
+    let ray = camera.viewport_to_world(camera_pos, cursor)?;
+    let distance = ray.intersect_plane(Vec3::ZERO, Vec3::NEG_Z)?;
+    Some(ray.get_point(distance).truncate())
+}
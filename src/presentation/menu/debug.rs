@@ -1,14 +1,38 @@
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
 use crate::app::settings::AppSettings;
+use crate::gameplay::mechanics::damage::Projectile;
+use crate::gameplay::objects::particles::Particle;
+use crate::gameplay::physics::RigidBody;
+use crate::gameplay::utils::Lifetime;
+use crate::presentation::objects::Star;
 use crate::utils::bevy_egui::*;
+use crate::utils::plugins::profiler::Profiler;
+use crate::utils::plugins::profiler::PROFILER_HISTORY;
 use bevy::diagnostic::*;
+use bevy::ecs::entity::Entities;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use std::time::Duration;
 
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(FrameTimeDiagnosticsPlugin::default())
-            .add_systems(Update, show_fps_count);
+            .add_console_command("perf", "", "Toggle performance overlay")
+            .add_systems(
+                Update,
+                (
+                    show_fps_count,
+                    (
+                        perf_command.run_if(on_event::<ConsoleCommand>()),
+                        enable_profiler.run_if(resource_changed::<AppSettings>()),
+                        show_perf_overlay,
+                    )
+                        .chain(),
+                ),
+            );
     }
 }
 
@@ -36,3 +60,143 @@ fn show_fps_count(
         ui.label(format!("FPS: {average:6.2} (avg per 20 frames)"));
     });
 }
+
+fn perf_command(mut commands: EventReader<ConsoleCommand>, mut settings: ResMut<AppSettings>) {
+    for _ in commands.read().filter(|command| command.is("perf")) {
+        settings.debug.show_perf = !settings.debug.show_perf;
+    }
+}
+
+fn enable_profiler(settings: Res<AppSettings>, mut profiler: ResMut<Profiler>) {
+    if profiler.is_enabled() != settings.debug.show_perf {
+        profiler.set_enabled(settings.debug.show_perf);
+    }
+}
+
+/// Numbers of entities of interest, shown in perf overlay
+#[derive(SystemParam)]
+struct EntityCounts<'w, 's> {
+    entities: &'w Entities,
+    projectiles: Query<'w, 's, (), With<Projectile>>,
+    particles: Query<'w, 's, (), (With<Particle>, With<Lifetime>)>,
+    stars: Query<'w, 's, (), With<Star>>,
+    bodies: Query<'w, 's, &'static RigidBody>,
+}
+
+impl<'w, 's> EntityCounts<'w, 's> {
+    fn rows(&self) -> [(&'static str, usize); 6] {
+        let dynamic = self
+            .bodies
+            .iter()
+            .filter(|body| **body == RigidBody::Dynamic)
+            .count();
+        [
+            ("Entities", self.entities.len() as usize),
+            ("Projectiles", self.projectiles.iter().count()),
+            ("Particles", self.particles.iter().count()),
+            ("Stars", self.stars.iter().count()),
+            ("Rapier bodies", self.bodies.iter().count()),
+            ("  dynamic", dynamic),
+        ]
+    }
+}
+
+fn show_perf_overlay(
+    mut egui_ctx: EguiContexts,
+    settings: Res<AppSettings>,
+    profiler: Res<Profiler>,
+    counts: EntityCounts,
+) {
+    if !settings.debug.show_perf {
+        return;
+    }
+
+    let ms = |time: Duration| time.as_secs_f32() * 1000.;
+
+    EguiPopup {
+        name: "show_perf_overlay",
+        anchor: egui::Align2::RIGHT_TOP,
+        offset: Vec2::new(0., 24.),
+        interactable: false,
+        ..default()
+    }
+    .show(egui_ctx.ctx_mut(), |ui| {
+        ui.style_mut().wrap = Some(false);
+
+        let last = profiler.frame_times.back().copied().unwrap_or_default();
+        let worst = profiler.frame_times.iter().max().copied().unwrap_or_default();
+        ui.label(format!("Frame: {:5.2} ms, worst: {:5.2} ms", ms(last), ms(worst)));
+        draw_frame_graph(ui, &profiler);
+
+        ui.separator();
+
+        egui::Grid::new("perf_entities").show(ui, |ui| {
+            for (name, count) in counts.rows() {
+                ui.label(name);
+                ui.label(count.to_string());
+                ui.end_row();
+            }
+        });
+
+        ui.separator();
+
+        egui::Grid::new("perf_scopes").show(ui, |ui| {
+            for header in ["System", "Calls", "Last", "Avg", "Max"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+
+            for (name, timings) in profiler.scopes.iter() {
+                ui.label(*name);
+                ui.label(timings.calls.to_string());
+                ui.label(format!("{:.3}", ms(timings.last())));
+                ui.label(format!("{:.3}", ms(timings.average())));
+                ui.label(format!("{:.3}", ms(timings.max())));
+                ui.end_row();
+            }
+        });
+        ui.small("Times are in milliseconds per frame");
+    });
+}
+
+/// Bar per frame; lines mark 60 and 30 FPS
+fn draw_frame_graph(ui: &mut egui::Ui, profiler: &Profiler) {
+    let target_60 = 1000. / 60.;
+    let target_30 = 1000. / 30.;
+
+    let bar_width = 1.;
+    let size = egui::vec2(PROFILER_HISTORY as f32 * bar_width, 60.);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    painter.rect_filled(rect, 0., egui::Color32::from_black_alpha(160));
+
+    let max_ms = profiler
+        .frame_times
+        .iter()
+        .map(|time| time.as_secs_f32() * 1000.)
+        .fold(target_30 * 1.2, f32::max);
+    let y_of = |ms: f32| rect.bottom() - rect.height() * (ms / max_ms).min(1.);
+
+    for (index, time) in profiler.frame_times.iter().enumerate() {
+        let ms = time.as_secs_f32() * 1000.;
+        let color = match ms {
+            ms if ms > target_30 => Color::RED,
+            ms if ms > target_60 * 1.1 => Color::ORANGE,
+            _ => Color::GREEN,
+        };
+        let x = rect.left() + index as f32 * bar_width;
+        painter.line_segment(
+            [egui::pos2(x, rect.bottom()), egui::pos2(x, y_of(ms))],
+            egui::Stroke::new(bar_width, color.to_egui()),
+        );
+    }
+
+    for target in [target_60, target_30] {
+        let y = y_of(target);
+        painter.line_segment(
+            [egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
+            egui::Stroke::new(1., Color::GRAY.with_a(0.7).to_egui()),
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use crate::utils::bevy_egui::*;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy_egui::EguiInput;
+use bevy_egui::EguiSet;
+
+/// Menu navigation with gamepad: d-pad moves focus between widgets, south button clicks.
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            gamepad_to_egui
+                .after(InputSystem)
+                .after(EguiSet::ProcessInput)
+                .before(EguiSet::BeginFrame),
+        );
+    }
+}
+
+/// Emulates keyboard keys which egui uses for focus navigation
+fn gamepad_to_egui(buttons: Res<Input<GamepadButton>>, mut egui_input: Query<&mut EguiInput>) {
+    let key = |button: GamepadButtonType| match button {
+        GamepadButtonType::DPadUp => Some(egui::Key::ArrowUp),
+        GamepadButtonType::DPadDown => Some(egui::Key::ArrowDown),
+        GamepadButtonType::DPadLeft => Some(egui::Key::ArrowLeft),
+        GamepadButtonType::DPadRight => Some(egui::Key::ArrowRight),
+        GamepadButtonType::South => Some(egui::Key::Enter),
+        _ => None,
+    };
+
+    let events: Vec<_> = buttons
+        .get_just_pressed()
+        .map(|button| (button, true))
+        .chain(buttons.get_just_released().map(|button| (button, false)))
+        .filter_map(|(button, pressed)| {
+            key(button.button_type).map(|key| egui::Event::Key {
+                key,
+                pressed,
+                repeat: false,
+                modifiers: default(),
+            })
+        })
+        .collect();
+
+    if events.is_empty() {
+        return;
+    }
+
+    for mut input in egui_input.iter_mut() {
+        input.events.extend(events.iter().cloned());
+    }
+}
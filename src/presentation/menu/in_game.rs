@@ -1,3 +1,4 @@
+use super::console::world_cursor;
 use super::level_editor::EditorEnabled;
 use super::states::MenuState;
 use super::ui_const::UiConst;
@@ -5,24 +6,32 @@ use crate::app::actions::action_axis_xy;
 use crate::app::actions::ActionPrompt;
 use crate::app::actions::AppActions;
 use crate::app::actions::PlayerActions;
+use crate::app::localization::Localization;
+use crate::gameplay::balance::Balance;
 use crate::gameplay::master::game_states::GameCommand;
 use crate::gameplay::master::level::current::CurrentLevel;
 use crate::gameplay::master::level::data::HALF_TILE;
-use crate::gameplay::master::level_progress::ImportantEnemy;
 use crate::gameplay::master::level_progress::LevelList;
+use crate::gameplay::master::level_progress::LevelProgressState;
+use crate::gameplay::mechanics::abilities::Abilities;
+use crate::gameplay::mechanics::abilities::Ability;
+use crate::gameplay::mechanics::abilities::Energy;
 use crate::gameplay::mechanics::damage::Dead;
 use crate::gameplay::mechanics::damage::Health;
 use crate::gameplay::mechanics::movement::MovementController;
 use crate::gameplay::mechanics::overload::Overload;
+use crate::gameplay::mechanics::status::StatusEffects;
+use crate::gameplay::mechanics::status::StatusKind;
 use crate::gameplay::mechanics::MechanicSet;
 use crate::gameplay::objects::player::Player;
-use crate::gameplay::objects::player::PLAYER_HEALTH;
+use crate::gameplay::physics::InterpolationSet;
 use crate::gameplay::utils::rotate_to_target;
 use crate::gameplay::utils::RotateToTarget;
 use crate::utils::bevy::commands::FallibleCommands;
 use crate::utils::bevy_egui::*;
 use crate::utils::math_algorithms::lerp;
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use leafwing_input_manager::action_state::ActionState;
 use std::time::Duration;
 
@@ -37,9 +46,7 @@ impl Plugin for HudPlugin {
                 (
                     draw_hud,
                     death_screen,
-                    player_input
-                        .before(MechanicSet::Input)
-                        .before(rotate_to_target),
+                    restart_input,
                     draw_overload,
                     tutorial,
                 )
@@ -47,6 +54,14 @@ impl Plugin for HudPlugin {
                 toggle_help_menu,
                 draw_help_menu.run_if(in_state(MenuState::Help)),
             ),
+        )
+        .add_systems(
+            FixedUpdate,
+            player_input
+                .after(InterpolationSet::Restore)
+                .before(MechanicSet::Input)
+                .before(rotate_to_target)
+                .run_if(in_state(MenuState::None).and_then(in_state(EditorEnabled::No))),
         );
     }
 }
@@ -60,14 +75,29 @@ fn died_since(died: Query<Entity, Added<Dead>>, mut commands: Commands, time: Re
     }
 }
 
+/// Player state shown in HUD
+type HudPlayer<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Health,
+        &'static Abilities,
+        &'static Energy,
+        &'static StatusEffects,
+    ),
+    (With<Player>, Without<Dead>),
+>;
+
 fn draw_hud(
     mut egui_ctx: EguiContexts,
     level: Res<CurrentLevel>,
     levels: Res<LevelList>,
-    player: Query<&Health, (With<Player>, Without<Dead>)>,
-    enemies: Query<(), (With<ImportantEnemy>, Without<Dead>)>,
+    player: HudPlayer,
+    balance: Res<Balance>,
+    progress: Res<LevelProgressState>,
+    loc: Res<Localization>,
 ) {
-    let Ok(health) = player.get_single() else { return; };
+    let Ok((health, abilities, energy, status)) = player.get_single() else { return; };
 
     EguiPopup {
         name: "draw_hud",
@@ -83,7 +113,7 @@ fn draw_hud(
         style.visuals.popup_shadow = egui::epaint::Shadow::NONE;
 
         egui::Frame::popup(style).show(ui, |ui| {
-            let hp = (health.value as f32 / PLAYER_HEALTH as f32 * 100.) as i32;
+            let hp = (health.value as f32 / balance.player_health as f32 * 100.) as i32;
             ui.visuals_mut().override_text_color = match hp {
                 _ if hp < 50 => Color::ORANGE_RED,
                 _ if hp < 75 => Color::YELLOW,
@@ -91,19 +121,59 @@ fn draw_hud(
             }
             .to_egui()
             .into();
-            ui.label(format!("HP: {hp:3}"));
+            ui.label(format!("{}: {hp:3}", loc.get("hud.health")));
+
+            ui.visuals_mut().override_text_color = Color::CYAN.to_egui().into();
+            ui.label(format!("{}: {:3}", loc.get("hud.energy"), energy.current as i32));
+
+            ui.horizontal(|ui| {
+                for ability in Ability::all() {
+                    let ready = abilities.is_ready(ability, energy, &balance);
+                    ui.visuals_mut().override_text_color = match ready {
+                        true => Color::WHITE,
+                        false => Color::GRAY,
+                    }
+                    .to_egui()
+                    .into();
+
+                    let level = abilities.level(ability);
+                    let cooldown = (abilities.t_cooldown(ability) * 10.).ceil() as usize;
+                    ui.small(format!(
+                        "{}{}{}",
+                        ability.name(&loc),
+                        "+".repeat(level as usize),
+                        ".".repeat(cooldown)
+                    ));
+                }
+            });
 
-            ui.visuals_mut().override_text_color = egui::Color32::from_gray(192).into();
-            ui.small(format!("\"{}\"", levels.name(&level.id)));
-
-            let count = enemies.iter().count();
-            if count == 0 {
-                ui.visuals_mut().override_text_color = Color::YELLOW_GREEN.to_egui().into();
-                ui.small(format!("No enemies left!"));
-            } else {
-                ui.visuals_mut().override_text_color = Color::WHITE.to_egui().into();
-                ui.small(format!("Enemies left: {count}"));
+            if StatusKind::all().any(|kind| status.has(kind)) {
+                ui.horizontal(|ui| {
+                    for kind in StatusKind::all().filter(|kind| status.has(*kind)) {
+                        ui.visuals_mut().override_text_color = match kind {
+                            StatusKind::Burning => Color::ORANGE_RED,
+                            StatusKind::Stunned => Color::YELLOW,
+                            StatusKind::Slowed => Color::CYAN,
+                        }
+                        .to_egui()
+                        .into();
+
+                        let stacks = status.stacks(kind);
+                        let time_left = (status.t_left(kind) * 10.).ceil() as usize;
+                        ui.small(format!(
+                            "{}{}{}",
+                            kind.name(&loc),
+                            if stacks > 1 { format!(" x{stacks}") } else { default() },
+                            ".".repeat(time_left)
+                        ));
+                    }
+                });
             }
+
+            ui.visuals_mut().override_text_color = egui::Color32::from_gray(192).into();
+            ui.small(format!("\"{}\"", levels.name(&level.id, &loc)));
+
+            draw_objectives(ui, &progress, &loc);
         });
     });
 }
@@ -113,6 +183,7 @@ fn death_screen(
     dead_player: Query<&DeadSince, With<Player>>,
     prompt: ActionPrompt<PlayerActions>,
     time: Res<Time<Real>>,
+    loc: Res<Localization>,
 ) {
     let fade_duration = Duration::from_millis(1000);
 
@@ -149,24 +220,48 @@ fn death_screen(
             ..default()
         }
         .show(egui_ctx.ctx_mut(), |ui| {
-            ui.label(egui::RichText::new("GAME OVER").heading().strong());
+            ui.label(egui::RichText::new(loc.get("death.title")).heading().strong());
 
-            ui.label("You were captured and brought back to the cell.\n");
+            ui.label(loc.get("death.text"));
 
-            ui.label(format!(
-                "[Press {} to restart level]",
-                prompt.get(PlayerActions::Restart)
+            ui.label(loc.format(
+                "death.restart",
+                &[("key", &prompt.get(PlayerActions::Restart))],
             ));
         });
     }
 }
 
+/// Max distance of the aim point from the player when aiming with gamepad
+const GAMEPAD_AIM_DISTANCE: f32 = 6.;
+
 fn player_input(
     actions: Res<ActionState<PlayerActions>>,
-    mut players: Query<(&mut RotateToTarget, &mut MovementController, &mut Player), Without<Dead>>,
-    mut game_commands: EventWriter<GameCommand>,
+    mut players: Query<
+        (
+            &GlobalTransform,
+            &mut RotateToTarget,
+            &mut MovementController,
+            &mut Player,
+        ),
+        Without<Dead>,
+    >,
+    camera: Query<(&GlobalTransform, &Camera)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut cursor_moved: EventReader<CursorMoved>,
+    mut gamepad_aim: Local<bool>,
 ) {
-    for (mut rotate, mut mvmt, mut player) in players.iter_mut() {
+    // last used device is used for aiming
+    let aim_dir = action_axis_xy(&actions, PlayerActions::Aim);
+    if aim_dir != Vec2::ZERO {
+        *gamepad_aim = true;
+    } else if cursor_moved.read().count() != 0 {
+        *gamepad_aim = false;
+    }
+
+    let world_cursor = world_cursor(&camera, &window);
+
+    for (pos, mut rotate, mut mvmt, mut player) in players.iter_mut() {
         if player.input_locked.finished() {
             let dir = action_axis_xy(&actions, PlayerActions::Movement);
 
@@ -174,15 +269,27 @@ fn player_input(
                 rotate.target_dir = dir;
                 player.input_walking = true;
             }
+            if aim_dir != Vec2::ZERO {
+                rotate.target_dir = aim_dir;
+            }
 
             mvmt.target_dir = dir;
 
             player.input_fire = actions.pressed(PlayerActions::Fire);
             player.input_pull = actions.pressed(PlayerActions::Pull);
             player.input_kick = actions.pressed(PlayerActions::Kick);
+            player.input_dash = actions.pressed(PlayerActions::Dash);
+            player.input_grab = actions.pressed(PlayerActions::Grab);
+            player.input_aim = match *gamepad_aim {
+                true => (aim_dir != Vec2::ZERO)
+                    .then(|| pos.translation().truncate() + aim_dir * GAMEPAD_AIM_DISTANCE),
+                false => world_cursor,
+            };
         }
     }
+}
 
+fn restart_input(actions: Res<ActionState<PlayerActions>>, mut game_commands: EventWriter<GameCommand>) {
     if actions.just_pressed(PlayerActions::Restart) {
         game_commands.send(GameCommand::Respawn);
     }
@@ -202,10 +309,30 @@ fn toggle_help_menu(
     }
 }
 
+fn draw_objectives(ui: &mut egui::Ui, progress: &LevelProgressState, loc: &Localization) {
+    for objective in &progress.objectives {
+        ui.visuals_mut().override_text_color = match objective.completed {
+            true => Color::YELLOW_GREEN,
+            false if objective.objective.secondary => Color::GRAY,
+            false => Color::WHITE,
+        }
+        .to_egui()
+        .into();
+        ui.small(objective.text(loc));
+    }
+
+    if progress.exit_unlocked() {
+        ui.visuals_mut().override_text_color = Color::YELLOW_GREEN.to_egui().into();
+        ui.small(loc.get("hud.exit_unlocked"));
+    }
+}
+
 fn draw_help_menu(
     mut egui_ctx: EguiContexts,
     prompt: ActionPrompt<PlayerActions>,
     app_prompt: ActionPrompt<AppActions>,
+    progress: Res<LevelProgressState>,
+    loc: Res<Localization>,
 ) {
     EguiPopup {
         name: "draw_help_menu",
@@ -213,40 +340,30 @@ fn draw_help_menu(
         ..default()
     }
     .show(egui_ctx.ctx_mut(), |ui| {
-        ui.heading("Escape!");
-        ui.label("1. Overload aliens");
-        ui.label("2. Reach elevator");
+        ui.heading(loc.get("help.title"));
+        draw_objectives(ui, &progress, &loc);
+        ui.label(loc.get("help.reach_elevator"));
         ui.label("");
 
-        ui.heading("Controls");
+        ui.heading(loc.get("help.controls"));
         egui::Grid::new("Controls").show(ui, |ui| {
-            ui.label(prompt.get(PlayerActions::Movement));
-            ui.label("Walk");
-            ui.end_row();
-
-            ui.label(prompt.get(PlayerActions::Fire));
-            ui.label("Emit fire");
-            ui.end_row();
-
-            ui.label(prompt.get(PlayerActions::Pull));
-            ui.label("Hold to pull objects, release to push");
-            ui.end_row();
-
-            ui.label(prompt.get(PlayerActions::Kick));
-            ui.label("Kick");
-            ui.end_row();
-
-            ui.label(prompt.get(PlayerActions::Restart));
-            ui.label("Restart level");
-            ui.end_row();
-
-            ui.label(prompt.get(PlayerActions::ToggleHelp));
-            ui.label("Open/close this window");
-            ui.end_row();
-
-            ui.label(app_prompt.get(AppActions::CloseMenu));
-            ui.label("Open/close menu");
-            ui.end_row();
+            for (prompt, key) in [
+                (prompt.get(PlayerActions::Movement), "help.walk"),
+                (prompt.get(PlayerActions::Aim), "help.aim"),
+                (prompt.get(PlayerActions::Fire), "help.fire"),
+                (prompt.get(PlayerActions::Pull), "help.pull"),
+                (prompt.get(PlayerActions::Kick), "help.kick"),
+                (prompt.get(PlayerActions::Dash), "help.dash"),
+                (prompt.get(PlayerActions::Grab), "help.grab"),
+                (prompt.get(PlayerActions::Zoom), "help.zoom"),
+                (prompt.get(PlayerActions::Restart), "help.restart"),
+                (prompt.get(PlayerActions::ToggleHelp), "help.toggle_help"),
+                (app_prompt.get(AppActions::CloseMenu), "help.toggle_menu"),
+            ] {
+                ui.label(prompt);
+                ui.label(loc.get(key));
+                ui.end_row();
+            }
         });
     });
 }
@@ -258,6 +375,7 @@ fn draw_overload(
     ui_const: UiConst,
     time: Res<Time<Real>>,
     dead_player: Query<(), (With<Player>, With<Dead>)>,
+    loc: Res<Localization>,
 ) {
     if !dead_player.is_empty() {
         return;
@@ -269,7 +387,8 @@ fn draw_overload(
         t if t >= 1. => Color::WHITE,
         _ => Color::YELLOW,
     };
-    let text = |t_overload: f32| format!("{:2}% CPU", (t_overload * 100.).min(100.) as i32);
+    let cpu = loc.get("hud.cpu");
+    let text = |t_overload: f32| format!("{:2}% {cpu}", (t_overload * 100.).min(100.) as i32);
 
     let text_size = 10.;
     let slider_width = 20.;
@@ -334,6 +453,7 @@ fn tutorial(
     current_level: Res<CurrentLevel>,
     player: Query<&GlobalTransform, With<Player>>,
     prompt: ActionPrompt<PlayerActions>,
+    loc: Res<Localization>,
 ) {
     if current_level.id == "01_cells" {
         let max_distance = 5_f32;
@@ -341,51 +461,29 @@ fn tutorial(
         let messages = [
             (
                 Vec2::new(-19., 0.),
-                format!(
-                    concat!("Press {} to walk\n", "Press {} to kick",),
-                    prompt.get(PlayerActions::Movement),
-                    prompt.get(PlayerActions::Kick)
+                loc.format(
+                    "tutorial.walk",
+                    &[
+                        ("walk", &prompt.get(PlayerActions::Movement)),
+                        ("kick", &prompt.get(PlayerActions::Kick)),
+                    ],
                 ),
             ),
             (
                 Vec2::new(-11., 0.),
-                format!(
-                    concat!(
-                        "Press {} to make\ncircle of fire and ignite barrels.\n",
-                        "That might take a few tries.\n",
-                        "\n",
-                        "Ignited barrels explode after some time.\n",
-                        "They also explode if they receive too much damage.\n",
-                        "\n",
-                        "Explosions damage both you and the barrels.",
-                    ),
-                    prompt.get(PlayerActions::Fire)
+                loc.format(
+                    "tutorial.fire",
+                    &[("fire", &prompt.get(PlayerActions::Fire))],
                 ),
             ),
             (
                 Vec2::new(-4., 0.),
-                format!(
-                    concat!(
-                        "Hold {} to pull nearby objects to you.\n",
-                        "\n",
-                        "On release objects are pushed away.\n",
-                        "\n",
-                        "Pull/push also affects projectiles.",
-                    ),
-                    prompt.get(PlayerActions::Pull)
+                loc.format(
+                    "tutorial.pull",
+                    &[("pull", &prompt.get(PlayerActions::Pull))],
                 ),
             ),
-            (
-                Vec2::new(1.5, 0.),
-                format!(concat!(
-                    "Robots are invincible, but their sensors can be overloaded.\n",
-                    "When their CPU load (red bar) reaches 100%, they will turn off.\n",
-                    "\n",
-                    "Explosions are the best way to overload robots.\n",
-                    "\n",
-                    "Exit elevator won't open until all robots are overloaded.",
-                ),),
-            ),
+            (Vec2::new(1.5, 0.), loc.get("tutorial.overload")),
         ];
 
         let player = player
@@ -406,7 +504,7 @@ fn tutorial(
                 ..default()
             }
             .show(egui_ctx.ctx_mut(), move |ui| {
-                ui.heading("Tutorial");
+                ui.heading(loc.get("message.tutorial"));
                 ui.small(text);
             });
         }
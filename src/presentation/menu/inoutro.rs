@@ -2,6 +2,7 @@ use super::states::CloseMenu;
 use super::states::MenuState;
 use crate::app::actions::ActionPrompt;
 use crate::app::actions::AppActions;
+use crate::app::localization::Localization;
 use crate::utils::bevy_egui::*;
 use crate::utils::plugins::load_assets::TrackAssets;
 use bevy::core_pipeline::clear_color::ClearColorConfig;
@@ -31,6 +32,7 @@ fn escape(
     app_prompt: ActionPrompt<AppActions>,
     mut close_menu: EventWriter<CloseMenu>,
     actions: Res<ActionState<AppActions>>,
+    loc: Res<Localization>,
 ) {
     EguiPopup {
         name: "show_escape",
@@ -41,9 +43,9 @@ fn escape(
     }
     .show(egui_ctx.ctx_mut(), |ui| {
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Max), |ui| {
-            ui.label(format!(
-                "Press {} to skip",
-                app_prompt.get(AppActions::CloseMenu)
+            ui.label(loc.format(
+                "inoutro.skip",
+                &[("key", &app_prompt.get(AppActions::CloseMenu))],
             ));
         });
     });
@@ -57,6 +59,7 @@ fn show_text(
     mut egui_ctx: EguiContexts,
     app_prompt: ActionPrompt<AppActions>,
     state: Res<State<MenuState>>,
+    loc: Res<Localization>,
 ) {
     EguiPopup {
         name: "intro",
@@ -73,33 +76,21 @@ fn show_text(
         egui::Frame::popup(style).show(ui, |ui| {
             match state.get() {
                 MenuState::Intro => {
-                    ui.label(concat!(
-                        "Jim was walking in a forest, when bright light appeared in the sky.\n",
-                        "Next thing he remembers, he is on an alien ship!\n",
-                        "\n",
-                        "Something has happened to Jim, now he can create fire and move\n",
-                        "objects with his will!\n",
-                        "\n",
-                        "Using his newfound powers, Jim escapes from his cell...\n",
-                    ));
+                    ui.label(loc.get("inoutro.intro"));
                 }
                 MenuState::Outro => {
-                    ui.label(concat!(
-                        "Jim reached the ship's bridge.\n",
-                        "No one was where, just lights on console blinking in total silence.\n",
-                        "\n",
-                        "After some trial and error Jim had figured the controls\n",
-                        "and set course back to Earth.\n",
-                    ));
-                    ui.heading("Thank you for playing!\n");
+                    ui.label(loc.get("inoutro.outro"));
+                    ui.heading(loc.get("inoutro.thanks"));
                 }
                 _ => error!("invalid state"),
             }
 
-            ui.label(format!(
-                "[Press {} or {} to continue]",
-                app_prompt.get(AppActions::Continue),
-                app_prompt.get(AppActions::CloseMenu)
+            ui.label(loc.format(
+                "inoutro.continue",
+                &[
+                    ("continue", &app_prompt.get(AppActions::Continue)),
+                    ("close", &app_prompt.get(AppActions::CloseMenu)),
+                ],
             ));
         });
     });
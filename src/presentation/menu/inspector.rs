@@ -0,0 +1,514 @@
+use super::console::world_cursor;
+use crate::app::actions::ActionPrompt;
+use crate::app::actions::AppActions;
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
+use crate::app::console::ConsoleOutput;
+use crate::app::settings::AppSettings;
+use crate::gameplay::master::level::data::LevelObjectId;
+use crate::gameplay::master::level::data::TILE_SIZE;
+use crate::gameplay::master::level::spawn::GameObject;
+use crate::gameplay::master::level::spawn::SpawnedBy;
+use crate::gameplay::mechanics::abilities::Energy;
+use crate::gameplay::mechanics::ai::Shoot;
+use crate::gameplay::mechanics::ai::Target;
+use crate::gameplay::mechanics::damage::Dead;
+use crate::gameplay::mechanics::damage::GodMode;
+use crate::gameplay::mechanics::damage::Health;
+use crate::gameplay::mechanics::damage::Invulnerable;
+use crate::gameplay::mechanics::overload::Overload;
+use crate::gameplay::mechanics::overload::OverloadSource;
+use crate::gameplay::mechanics::status::StatusEffects;
+use crate::gameplay::mechanics::status::StatusKind;
+use crate::gameplay::objects::barrels::Barrel;
+use crate::gameplay::objects::barrels::OnFire;
+use crate::gameplay::objects::enemy::Enemy;
+use crate::gameplay::objects::player::Player;
+use crate::gameplay::objects::terrain::TerrainDecor;
+use crate::gameplay::objects::terrain::TerrainFloor;
+use crate::gameplay::objects::terrain::TerrainLight;
+use crate::gameplay::objects::terrain::TerrainWall;
+use crate::gameplay::physics::Velocity;
+use crate::presentation::AdvancedGizmos;
+use crate::utils::bevy::commands::FallibleCommands;
+use crate::utils::bevy_egui::*;
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use leafwing_input_manager::prelude::ActionState;
+use std::time::Duration;
+
+/// Shows and edits components of the picked entity. Only available in developer mode.
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inspector>()
+            .add_console_command("inspect", "[ENTITY]", "Open entity in inspector, or close it")
+            .add_systems(
+                Update,
+                (
+                    (
+                        pick_entity,
+                        console_command.run_if(on_event::<ConsoleCommand>()),
+                    ),
+                    list_components,
+                    (draw_inspector, show_gizmos),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Picked entity must be that close to the cursor
+const PICK_RADIUS: f32 = TILE_SIZE * 0.75;
+
+#[derive(Resource, Default)]
+struct Inspector {
+    selected: Option<Entity>,
+    /// Names of all components of the selected entity
+    components: Vec<String>,
+}
+
+/// Game objects which can be picked; terrain is picked only if there is nothing else
+type Pickable<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static GlobalTransform,
+        (
+            Has<TerrainWall>,
+            Has<TerrainFloor>,
+            Has<TerrainLight>,
+            Has<TerrainDecor>,
+        ),
+    ),
+    With<GameObject>,
+>;
+
+/// Game object closest to the cursor
+fn hovered_entity(cursor: Option<Vec2>, objects: &Pickable) -> Option<Entity> {
+    let cursor = cursor?;
+    objects
+        .iter()
+        .filter_map(|(entity, transform, (wall, floor, light, decor))| {
+            let terrain = wall || floor || light || decor;
+            let distance = transform.translation().truncate().distance(cursor);
+            (distance < PICK_RADIUS).then_some((terrain, distance, entity))
+        })
+        .min_by(|a, b| (a.0, a.1).partial_cmp(&(b.0, b.1)).unwrap())
+        .map(|(_, _, entity)| entity)
+}
+
+fn pick_entity(
+    actions: Res<ActionState<AppActions>>,
+    settings: Res<AppSettings>,
+    mut inspector: ResMut<Inspector>,
+    objects: Pickable,
+    camera: Query<(&GlobalTransform, &Camera)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if !settings.debug.developer_mode {
+        if inspector.selected.is_some() {
+            inspector.selected = None;
+        }
+        return;
+    }
+
+    if actions.just_pressed(AppActions::Inspect) {
+        if let Some(entity) = hovered_entity(world_cursor(&camera, &window), &objects) {
+            inspector.selected = Some(entity);
+        }
+    }
+}
+
+fn console_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut inspector: ResMut<Inspector>,
+    entities: &Entities,
+) {
+    for command in commands.read().filter(|command| command.is("inspect")) {
+        let result = command.arg_opt::<u32>(0).and_then(|index| match index {
+            Some(index) => {
+                let entity = entities
+                    .resolve_from_id(index)
+                    .filter(|entity| entities.contains(*entity))
+                    .ok_or_else(|| format!("no entity with index {index}"))?;
+                inspector.selected = Some(entity);
+                Ok(format!("inspecting {entity:?}"))
+            }
+            None => {
+                inspector.selected = None;
+                Ok("inspector closed".to_string())
+            }
+        });
+        output.send(ConsoleOutput::result(result));
+    }
+}
+
+/// Component names are only available with full world access
+fn list_components(world: &mut World) {
+    let Some(entity) = world.resource::<Inspector>().selected else { return; };
+
+    let components = match world.get_entity(entity) {
+        Some(_) => world
+            .inspect_entity(entity)
+            .iter()
+            .map(|info| info.name().to_string())
+            .collect(),
+        None => vec![],
+    };
+
+    let mut inspector = world.resource_mut::<Inspector>();
+    if components.is_empty() {
+        inspector.selected = None;
+    }
+    inspector.components = components;
+}
+
+fn show_gizmos(
+    inspector: Res<Inspector>,
+    settings: Res<AppSettings>,
+    objects: Pickable,
+    camera: Query<(&GlobalTransform, &Camera)>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut gizmos: AdvancedGizmos,
+) {
+    if !settings.debug.developer_mode {
+        return;
+    }
+
+    let hovered = hovered_entity(world_cursor(&camera, &window), &objects);
+    if let Some(entity) = hovered.filter(|entity| Some(*entity) != inspector.selected) {
+        gizmos.entity_text(entity, format!("?{}", entity.index()));
+    }
+    if let Some(entity) = inspector.selected {
+        gizmos.entity_text(entity, format!("[{}]", entity.index()));
+    }
+}
+
+/// All components which can be shown or edited
+type Inspected = (
+    (Option<&'static mut Transform>, Option<&'static mut Velocity>),
+    (
+        Option<&'static mut Health>,
+        Option<&'static Invulnerable>,
+        Has<GodMode>,
+        Has<Dead>,
+    ),
+    (
+        Option<&'static mut Overload>,
+        Option<&'static mut OverloadSource>,
+        Option<&'static mut Energy>,
+        Option<&'static StatusEffects>,
+    ),
+    (
+        Option<&'static mut Target>,
+        Option<&'static mut Shoot>,
+        Option<&'static mut OnFire>,
+    ),
+    (
+        Option<&'static Player>,
+        Option<&'static Enemy>,
+        Option<&'static Barrel>,
+        Option<&'static LevelObjectId>,
+        Option<&'static SpawnedBy>,
+    ),
+);
+
+fn draw_inspector(
+    mut egui_ctx: EguiContexts,
+    mut inspector: ResMut<Inspector>,
+    mut inspected: Query<Inspected>,
+    hierarchy: Query<(Option<&Name>, Option<&Parent>, Option<&Children>)>,
+    mut commands: Commands,
+    prompt: ActionPrompt<AppActions>,
+    time: Res<Time>,
+) {
+    let Some(entity) = inspector.selected else { return; };
+    let Ok((
+        (transform, velocity),
+        (health, invulnerable, god_mode, dead),
+        (overload, overload_source, energy, status),
+        (target, shoot, on_fire),
+        (player, enemy, barrel, level_id, spawned_by),
+    )) = inspected.get_mut(entity) else { return; };
+
+    let name = |entity: Entity| match hierarchy.get(entity).ok().and_then(|v| v.0) {
+        Some(name) => format!("{entity:?} \"{name}\""),
+        None => format!("{entity:?}"),
+    };
+
+    let mut select = None;
+    let mut close = false;
+
+    EguiPopup {
+        name: "draw_inspector",
+        anchor: egui::Align2::RIGHT_TOP,
+        offset: Vec2::new(-10., 30.),
+        ..default()
+    }
+    .show(egui_ctx.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            ui.heading(name(entity));
+            close = ui.button("Close").clicked();
+        });
+        ui.label(format!(
+            "Press {} to pick entity under cursor",
+            prompt.get(AppActions::Inspect)
+        ));
+
+        egui::ScrollArea::vertical()
+            .max_height(ui.ctx().screen_rect().height() * 0.8)
+            .show(ui, |ui| {
+                ui.collapsing("Hierarchy", |ui| {
+                    let Ok((_, parent, children)) = hierarchy.get(entity) else { return; };
+                    if let Some(parent) = parent {
+                        ui.label("Parent:");
+                        if ui.button(name(parent.get())).clicked() {
+                            select = Some(parent.get());
+                        }
+                    }
+                    for child in children.iter().flat_map(|children| children.iter()) {
+                        if ui.button(name(*child)).clicked() {
+                            select = Some(*child);
+                        }
+                    }
+                });
+
+                ui.collapsing("Components", |ui| {
+                    for component in inspector.components.iter() {
+                        ui.label(component);
+                    }
+                });
+
+                if let Some(mut transform) = transform {
+                    component(ui, "Transform", |ui| {
+                        let mut pos = transform.translation.truncate();
+                        let mut angle = transform.rotation.to_euler(EulerRot::ZYX).0.to_degrees();
+                        if edit_vec2(ui, "position", &mut pos) {
+                            transform.translation = pos.extend(transform.translation.z);
+                        }
+                        if edit_f32(ui, "rotation (degrees)", &mut angle) {
+                            transform.rotation = Quat::from_rotation_z(angle.to_radians());
+                        }
+                    });
+                }
+                if let Some(mut velocity) = velocity {
+                    component(ui, "Velocity", |ui| {
+                        let (mut linvel, mut angvel) = (velocity.linvel, velocity.angvel);
+                        if edit_vec2(ui, "linear", &mut linvel) {
+                            velocity.linvel = linvel;
+                        }
+                        if edit_f32(ui, "angular", &mut angvel) {
+                            velocity.angvel = angvel;
+                        }
+                    });
+                }
+
+                if let Some(mut health) = health {
+                    component(ui, "Health", |ui| {
+                        let mut value = health.value;
+                        if edit_u32(ui, "value", &mut value) {
+                            health.value = value;
+                        }
+                        show(ui, "type", format!("{:?}", health.ty));
+                    });
+                }
+                if let Some(invulnerable) = invulnerable {
+                    component(ui, "Invulnerable", |ui| {
+                        show(ui, "type", format!("{:?}", invulnerable.ty));
+                        show(ui, "remaining", format_duration(invulnerable.remaining()));
+                    });
+                }
+                component(ui, "Flags", |ui| {
+                    let mut value = god_mode;
+                    ui.label("god mode");
+                    if ui.checkbox(&mut value, "").changed() {
+                        match value {
+                            true => commands.try_insert(entity, GodMode),
+                            false => commands.try_remove::<GodMode>(entity),
+                        }
+                    }
+                    ui.end_row();
+                    show(ui, "dead", dead.to_string());
+                });
+
+                if let Some(mut overload) = overload {
+                    component(ui, "Overload", |ui| {
+                        let (mut current, mut max) = (overload.current, overload.max);
+                        if edit_f32(ui, "current", &mut current) {
+                            overload.current = current;
+                        }
+                        if edit_f32(ui, "max", &mut max) {
+                            overload.max = max;
+                        }
+                    });
+                }
+                if let Some(mut source) = overload_source {
+                    component(ui, "OverloadSource", |ui| {
+                        let mut power = source.power;
+                        if edit_f32(ui, "power", &mut power) {
+                            source.power = power;
+                        }
+                    });
+                }
+                if let Some(mut energy) = energy {
+                    component(ui, "Energy", |ui| {
+                        let (mut current, mut max, mut regen) =
+                            (energy.current, energy.max, energy.regen);
+                        if edit_f32(ui, "current", &mut current) {
+                            energy.current = current;
+                        }
+                        if edit_f32(ui, "max", &mut max) {
+                            energy.max = max;
+                        }
+                        if edit_f32(ui, "regen", &mut regen) {
+                            energy.regen = regen;
+                        }
+                    });
+                }
+                if let Some(status) = status {
+                    component(ui, "StatusEffects", |ui| {
+                        for kind in StatusKind::all().filter(|kind| status.has(*kind)) {
+                            show(
+                                ui,
+                                &format!("{kind:?}"),
+                                format!(
+                                    "x{} ({:.0}% left)",
+                                    status.stacks(kind),
+                                    status.t_left(kind) * 100.
+                                ),
+                            );
+                        }
+                    });
+                }
+
+                if let Some(mut target) = target {
+                    component(ui, "Target", |ui| {
+                        let distance = target.distance();
+                        show(
+                            ui,
+                            "distance",
+                            distance.map(|v| format!("{v:.2}")).unwrap_or_else(|| "-".to_string()),
+                        );
+                        let mut visible = target.visible_for.as_secs_f32();
+                        if edit_f32(ui, "visible for", &mut visible) {
+                            target.visible_for = Duration::from_secs_f32(visible.max(0.));
+                        }
+                        let mut invisible = target.invisible_for.as_secs_f32();
+                        if edit_f32(ui, "invisible for", &mut invisible) {
+                            target.invisible_for = Duration::from_secs_f32(invisible.max(0.));
+                        }
+                    });
+                }
+                if let Some(mut shoot) = shoot {
+                    component(ui, "Shoot", |ui| {
+                        let mut period = shoot.period.as_secs_f32();
+                        if edit_f32(ui, "period", &mut period) {
+                            shoot.period = Duration::from_secs_f32(period.max(0.001));
+                        }
+                        let mut damage = shoot.projectile.damage;
+                        if edit_u32(ui, "damage", &mut damage) {
+                            shoot.projectile.damage = damage;
+                        }
+                    });
+                }
+                if let Some(mut on_fire) = on_fire {
+                    component(ui, "OnFire", |ui| {
+                        let now = time.elapsed();
+                        show(
+                            ui,
+                            "burning for",
+                            format_duration(now.saturating_sub(on_fire.started_at)),
+                        );
+                        let mut left = on_fire.explode_at.saturating_sub(now).as_secs_f32();
+                        if edit_f32(ui, "explodes in", &mut left) {
+                            on_fire.explode_at = now + Duration::from_secs_f32(left.max(0.));
+                        }
+                    });
+                }
+
+                if player.is_some() || enemy.is_some() || barrel.is_some() {
+                    component(ui, "Object", |ui| {
+                        if let Some(player) = player {
+                            show(ui, "player", format!("{:?}", player.state));
+                        }
+                        if let Some(enemy) = enemy {
+                            show(ui, "enemy", format!("{enemy:?}"));
+                        }
+                        if let Some(barrel) = barrel {
+                            show(ui, "barrel", format!("{barrel:?}"));
+                        }
+                    });
+                }
+                if level_id.is_some() || spawned_by.is_some() {
+                    component(ui, "Level", |ui| {
+                        if let Some(id) = level_id {
+                            show(ui, "object ID", format!("{id:?}"));
+                        }
+                        if let Some(spawned_by) = spawned_by {
+                            show(ui, "spawned by", format!("{:?}", spawned_by.0));
+                        }
+                    });
+                }
+            });
+    });
+
+    if close {
+        inspector.selected = None;
+    } else if let Some(entity) = select {
+        inspector.selected = Some(entity);
+    }
+}
+
+/// Collapsible grid with component fields
+fn component(ui: &mut egui::Ui, name: &str, add_contents: impl FnOnce(&mut egui::Ui)) {
+    egui::CollapsingHeader::new(name)
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new(name).num_columns(2).show(ui, add_contents);
+        });
+}
+
+fn show(ui: &mut egui::Ui, label: &str, value: String) {
+    ui.label(label);
+    ui.label(value);
+    ui.end_row();
+}
+
+/// Returns true if value was changed
+fn edit_f32(ui: &mut egui::Ui, label: &str, value: &mut f32) -> bool {
+    ui.label(label);
+    let changed = ui.add(egui::DragValue::new(value).speed(0.05)).changed();
+    ui.end_row();
+    changed
+}
+
+/// Returns true if value was changed
+fn edit_u32(ui: &mut egui::Ui, label: &str, value: &mut u32) -> bool {
+    ui.label(label);
+    let changed = ui.add(egui::DragValue::new(value)).changed();
+    ui.end_row();
+    changed
+}
+
+/// Returns true if value was changed
+fn edit_vec2(ui: &mut egui::Ui, label: &str, value: &mut Vec2) -> bool {
+    ui.label(label);
+    let changed = ui
+        .horizontal(|ui| {
+            let x = ui.add(egui::DragValue::new(&mut value.x).speed(0.05));
+            let y = ui.add(egui::DragValue::new(&mut value.y).speed(0.05));
+            x.changed() || y.changed()
+        })
+        .inner;
+    ui.end_row();
+    changed
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f32())
+}
@@ -8,9 +8,14 @@ use crate::gameplay::master::level::data::*;
 use crate::gameplay::master::level::spawn::SpawnObject;
 use crate::gameplay::master::script_points::EnemySpawner;
 use crate::gameplay::objects::barrels::Barrel;
+use crate::gameplay::objects::conveyor::BeltSwitch;
 use crate::gameplay::objects::conveyor::Conveyor;
-use crate::gameplay::objects::conveyor::ConveyorOutput;
+use crate::gameplay::objects::conveyor::ChuteObject;
+use crate::gameplay::objects::conveyor::ChutePreset;
+use crate::gameplay::objects::conveyor::ChuteSchedule;
+use crate::gameplay::objects::conveyor::ChuteTrigger;
 use crate::gameplay::objects::elevators::Elevator;
+use crate::gameplay::objects::enemy::Enemy;
 use crate::gameplay::objects::terrain::TerrainDecor;
 use crate::gameplay::objects::terrain::TerrainFloor;
 use crate::gameplay::objects::terrain::TerrainLight;
@@ -322,10 +327,116 @@ fn simple_slider_field(
         .changed();
 }
 
+fn channel_field(ui: &mut egui::Ui, changed: &mut bool, value: &mut u8) {
+    ui.horizontal(|ui| {
+        *changed |= ui.add(egui::DragValue::new(value)).changed();
+        ui.label("Switch channel");
+    });
+}
+
+fn range_field<T: egui::emath::Numeric>(
+    ui: &mut egui::Ui,
+    changed: &mut bool,
+    name: &str,
+    value: &mut (T, T),
+) {
+    ui.horizontal(|ui| {
+        for value in [&mut value.0, &mut value.1] {
+            let field = egui::DragValue::new(value)
+                .speed(0.1)
+                .clamp_range(0.0..=f32::MAX);
+            *changed |= ui.add(field).changed();
+        }
+        ui.label(name);
+    });
+    if value.1 < value.0 {
+        value.1 = value.0;
+    }
+}
+
+fn edit_chute_schedule(ui: &mut egui::Ui, changed: &mut bool, schedule: &mut ChuteSchedule) {
+    ui.small("Presets:");
+    ui.horizontal_wrapped(|ui| {
+        for preset in ChutePreset::all() {
+            if ui.button(preset.name()).clicked() {
+                *schedule = preset.schedule();
+                *changed = true;
+            }
+        }
+    });
+
+    ui.small("Objects and weights:");
+    let mut remove = None;
+    for (index, (object, weight)) in schedule.objects.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            *changed |= ui.add(egui::DragValue::new(weight)).changed();
+            ui.label(format!("{object:?}"));
+            if ui.small_button("X").clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        schedule.objects.remove(index);
+        *changed = true;
+    }
+    ui.horizontal(|ui| {
+        for object in [
+            ChuteObject::Barrel(Barrel::Fire),
+            ChuteObject::Enemy(Enemy::Spam),
+        ] {
+            if ui.button(format!("+ {object:?}")).clicked() {
+                schedule.objects.push((object, 1));
+                *changed = true;
+            }
+        }
+    });
+
+    range_field(ui, changed, "Burst size", &mut schedule.burst);
+    range_field(ui, changed, "Burst interval, s", &mut schedule.burst_interval);
+    range_field(ui, changed, "Spawn interval, s", &mut schedule.spawn_interval);
+
+    let mut has_max_alive = schedule.max_alive.is_some();
+    if ui.checkbox(&mut has_max_alive, "Limit alive count").changed() {
+        schedule.max_alive = has_max_alive.then_some(4);
+        *changed = true;
+    }
+    if let Some(max_alive) = schedule.max_alive.as_mut() {
+        ui.horizontal(|ui| {
+            *changed |= ui.add(egui::DragValue::new(max_alive)).changed();
+            ui.label("Max alive");
+        });
+    }
+
+    ui.small("Trigger:");
+    let trigger = &mut schedule.trigger;
+    ui.horizontal(|ui| {
+        for (value, name) in [
+            (ChuteTrigger::Always, "Always"),
+            (ChuteTrigger::Switch(0), "Switch"),
+            (ChuteTrigger::PlayerNear(10.), "Player near"),
+        ] {
+            let selected = std::mem::discriminant(trigger) == std::mem::discriminant(&value);
+            if ui.radio(selected, name).clicked() && !selected {
+                *trigger = value;
+                *changed = true;
+            }
+        }
+    });
+    match trigger {
+        ChuteTrigger::Always => (),
+        ChuteTrigger::Switch(channel) => channel_field(ui, changed, channel),
+        ChuteTrigger::PlayerNear(distance) => {
+            simple_slider_field(ui, changed, " distance", distance, 1. ..=30.)
+        }
+    }
+}
+
 fn forced_center_align(object: &LevelObjectData) -> bool {
     match object {
         LevelObjectData::ScriptPoint(_)
         | LevelObjectData::EnemySpawner(_)
+        | LevelObjectData::Checkpoint(_)
         | LevelObjectData::TerrainFloor(_) => true,
         _ => false,
     }
@@ -346,6 +457,7 @@ fn make_object(ui: &mut egui::Ui) -> Option<LevelObjectData> {
         ("Script point", LevelObjectData::ScriptPoint(default())),
         ("Enemy spawner", LevelObjectData::EnemySpawner(default())),
         ("Elevator", LevelObjectData::Elevator(default())),
+        ("Checkpoint", LevelObjectData::Checkpoint(default())),
         ("", LevelObjectData::None),
         ("Barrel", LevelObjectData::Barrel(Barrel::Fire)),
         (
@@ -353,7 +465,7 @@ fn make_object(ui: &mut egui::Ui) -> Option<LevelObjectData> {
             LevelObjectData::TerrainDecor(TerrainDecor::ClosedPipe),
         ),
         ("Unique", LevelObjectData::UniqueDecor(UniqueDecor::Cannon)),
-        ("Conveyor", LevelObjectData::Conveyor(Conveyor::Belt)),
+        ("Conveyor", LevelObjectData::Conveyor(Conveyor::DEFAULT_BELT)),
         ("", LevelObjectData::None),
         ("Wall", LevelObjectData::TerrainWall(default())),
         ("Floor", LevelObjectData::TerrainFloor(default())),
@@ -453,6 +565,8 @@ fn edit_object(
             *changed |= ui.radio_value(object, Elevator::Exit, "Exit").changed();
         }
 
+        LevelObjectData::Checkpoint(_) => (),
+
         //
         LevelObjectData::Barrel(object) => {
             *changed |= ui.radio_value(object, Barrel::Fire, "Fire").changed();
@@ -486,39 +600,73 @@ fn edit_object(
         }
 
         LevelObjectData::Conveyor(object) => {
-            *changed |= ui.radio_value(object, Conveyor::Belt, "Belt").changed();
+            if ui
+                .radio(matches!(object, Conveyor::Belt { .. }), "Belt")
+                .clicked()
+            {
+                *object = Conveyor::DEFAULT_BELT;
+                *changed = true;
+            }
+            if ui
+                .radio(matches!(object, Conveyor::Switch { .. }), "Switch")
+                .clicked()
+            {
+                *object = Conveyor::Switch { channel: 0 };
+                *changed = true;
+            }
             *changed |= ui
                 .radio_value(object, Conveyor::EndChute, "End chute")
                 .changed();
 
-            *changed |= ui
-                .radio_value(
-                    object,
-                    Conveyor::StartChute(ConveyorOutput::BarrelsRareGroup),
-                    "Start chute: Barrels rare group",
-                )
-                .changed();
-            *changed |= ui
-                .radio_value(
-                    object,
-                    Conveyor::StartChute(ConveyorOutput::BarrelsFrequent),
-                    "Start chute: Barrels Frequent",
-                )
-                .changed();
-            *changed |= ui
-                .radio_value(
-                    object,
-                    Conveyor::StartChute(ConveyorOutput::RandomEnemies),
-                    "Start chute: Random Enemies",
-                )
-                .changed();
-            *changed |= ui
-                .radio_value(
-                    object,
-                    Conveyor::StartChute(ConveyorOutput::Random),
-                    "Start chute: Random",
-                )
-                .changed();
+            if ui
+                .radio(matches!(object, Conveyor::StartChute(..)), "Start chute")
+                .clicked()
+            {
+                *object = Conveyor::StartChute(default());
+                *changed = true;
+            }
+
+            match object {
+                Conveyor::Belt {
+                    speed,
+                    channel,
+                    switched,
+                } => {
+                    ui.label("");
+                    simple_slider_field(ui, changed, " speed", speed, 0.5..=10.);
+
+                    let mut has_channel = channel.is_some();
+                    if ui.checkbox(&mut has_channel, "Controlled by switch").changed() {
+                        *channel = has_channel.then_some(0);
+                        *changed = true;
+                    }
+
+                    if let Some(channel) = channel {
+                        channel_field(ui, changed, channel);
+
+                        ui.small("When switch is on:");
+                        ui.horizontal(|ui| {
+                            for (value, name) in [
+                                (BeltSwitch::Reverse, "Reverse"),
+                                (BeltSwitch::TurnLeft, "Turn left"),
+                                (BeltSwitch::TurnRight, "Turn right"),
+                                (BeltSwitch::Stop, "Stop"),
+                            ] {
+                                *changed |= ui.radio_value(switched, value, name).changed();
+                            }
+                        });
+                    }
+                }
+                Conveyor::Switch { channel } => {
+                    ui.label("");
+                    channel_field(ui, changed, channel);
+                }
+                Conveyor::StartChute(schedule) => {
+                    ui.label("");
+                    edit_chute_schedule(ui, changed, schedule);
+                }
+                _ => (),
+            }
         }
 
         //
@@ -867,11 +1015,19 @@ fn draw_labels(
             LevelObjectData::ScriptPoint(object) => (1, format!("SP:{}", object.id)),
             LevelObjectData::EnemySpawner(_object) => (1, format!("Enemy")),
             LevelObjectData::Elevator(_object) => (1, format!("Elevator")),
+            LevelObjectData::Checkpoint(_object) => (1, "Checkpoint".to_string()),
             //
             LevelObjectData::Barrel(_object) => (2, format!("Barrel")),
             LevelObjectData::TerrainDecor(_object) => (2, format!("Barrel")),
             LevelObjectData::UniqueDecor(_object) => (2, format!("Decor")),
-            LevelObjectData::Conveyor(_object) => (4, format!("Conveyor")),
+            LevelObjectData::Conveyor(object) => match object {
+                Conveyor::Belt {
+                    channel: Some(channel),
+                    ..
+                } => (4, format!("Belt #{channel}")),
+                Conveyor::Switch { channel } => (4, format!("Switch #{channel}")),
+                _ => (4, "Conveyor".to_string()),
+            },
             //
             LevelObjectData::TerrainWall(_) => (2, "Wall".to_string()),
             LevelObjectData::TerrainFloor(_) => (3, "Floor".to_string()),
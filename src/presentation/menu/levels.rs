@@ -1,5 +1,6 @@
 use super::states::MenuState;
 use super::ui_const::UiConst;
+use crate::app::localization::Localization;
 use crate::app::scores::Scores;
 use crate::app::settings::AppSettings;
 use crate::gameplay::master::game_states::GameCommand;
@@ -33,6 +34,7 @@ fn level_select(
     scores: Res<Scores>,
     levels: Res<LevelList>,
     settings: Res<AppSettings>,
+    loc: Res<Localization>,
 ) {
     let level_editor = settings.debug.developer_mode;
 
@@ -43,7 +45,7 @@ fn level_select(
     .show(egui_ctx.ctx_mut(), |ui| {
         egui::ScrollArea::vertical().show(ui, |ui| {
             for id in levels.all() {
-                let name = levels.name(id);
+                let name = levels.name(id, &loc);
                 let completed = scores.completed_levels.contains(id);
 
                 ui.horizontal(|ui| {
@@ -64,7 +66,26 @@ fn level_select(
                     }
 
                     if completed {
-                        ui.label("(Completed)");
+                        match scores.completed_on.get(id) {
+                            Some(difficulty) => ui.label(loc.format(
+                                "levels.completed_on",
+                                &[("difficulty", &difficulty.name(&loc))],
+                            )),
+                            None => ui.label(loc.get("levels.completed")),
+                        };
+
+                        let total = levels.data(id).objectives().len();
+                        if total > 1 {
+                            let done = scores
+                                .completed_objectives
+                                .get(id)
+                                .map(|done| done.len())
+                                .unwrap_or_default();
+                            ui.label(loc.format(
+                                "levels.objectives",
+                                &[("done", &done), ("total", &total)],
+                            ));
+                        }
                     }
                 });
             }
@@ -89,6 +110,7 @@ fn level_loading(
     mut level_commands: EventWriter<LevelCommand>,
     levels: Res<LevelList>,
     ui_const: UiConst,
+    loc: Res<Localization>,
 ) {
     let fade_duration = Duration::from_millis(1500);
     let text_size = 64. * ui_const.scale();
@@ -168,7 +190,7 @@ fn level_loading(
             .show(egui_ctx.ctx_mut(), |ui| {
                 // level name
                 ui.visuals_mut().override_text_color = Color::WHITE.with_a(alpha).to_egui().into();
-                ui.label(egui::RichText::new(levels.name(id)).size(text_size));
+                ui.label(egui::RichText::new(levels.name(id, &loc)).size(text_size));
             });
         }
     }
@@ -1,182 +1,315 @@
+use crate::app::actions::ActionPrompt;
+use crate::app::actions::AppActions;
+use crate::app::console::ConsoleAppExt;
+use crate::app::console::ConsoleCommand;
+use crate::app::settings::AppSettings;
 use crate::utils::bevy_egui::*;
-use crate::{
-    app::settings::AppSettings,
-    utils::{
-        math_algorithms::map_linear_range, misc_utils::invert_color,
-        plugins::log_plugin::LogMessage,
-    },
-};
-use bevy::{log::Level, prelude::*};
-use std::time::Duration;
+use crate::utils::misc_utils::invert_color;
+use crate::utils::plugins::log_plugin::LogFilter;
+use crate::utils::plugins::log_plugin::LogMessage;
+use bevy::log::Level;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
 
+/// Log window with filters and search
 pub struct LogPlugin;
 
 impl Plugin for LogPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, log_messages);
+        app.init_resource::<LogViewer>()
+            .add_console_command("log", "", "Toggle log window")
+            .add_systems(
+                Update,
+                (
+                    receive_messages,
+                    toggle_log_window,
+                    draw_log_window,
+                    notify_errors,
+                )
+                    .chain(),
+            );
     }
 }
 
-#[derive(Default)]
-struct DebugLog {
-    // circular buffer: message, time when added
-    messages: Vec<(Option<LogMessage>, Duration)>,
-    // index into messages where to add next message
-    next: usize,
+/// Max number of lines kept
+const MAX_LINES: usize = 5000;
 
-    // has any non-expired messages
-    has_any: bool,
-}
-
-fn log_messages(
-    mut egui_ctx: EguiContexts,
-    time: Res<Time<Real>>,
-    mut messages: ResMut<Events<LogMessage>>,
-    mut data: Local<DebugLog>,
-    settings: Res<AppSettings>,
-) {
-    const DURATION: f32 = 5.; // how long message is shown
-    const FADE_START: f32 = 4.; // when message starts fade to transparency
-    const FONT_SIZE_INCREASE: f32 = 5.; // no idea how to read real text size, so just use this
-    let background_color = Color::BLACK.with_a(0.7);
-
-    const IGNORED_MESSAGES: &'static [&'static str] = &[];
+const LEVELS: [Level; 5] = [
+    Level::ERROR,
+    Level::WARN,
+    Level::INFO,
+    Level::DEBUG,
+    Level::TRACE,
+];
 
-    let level_color = |level: Level| match level {
+fn level_color(level: Level) -> Color {
+    match level {
         Level::TRACE => Color::WHITE,
         Level::DEBUG => Color::CYAN,
         Level::INFO => Color::GREEN,
         Level::WARN => Color::ORANGE_RED,
         Level::ERROR => Color::RED,
-    };
+    }
+}
 
-    EguiPopup {
-        name: "log_messages",
-        background: false,
-        anchor: egui::Align2::LEFT_TOP,
-        order: egui::Order::Tooltip,
-        interactable: false,
-        ..default()
+#[derive(Resource)]
+struct LogViewer {
+    open: bool,
+    /// Shown as bottom panel instead of a window
+    docked: bool,
+
+    lines: VecDeque<LogMessage>,
+    /// Messages received while paused, oldest are dropped after [`MAX_LINES`]
+    held: VecDeque<LogMessage>,
+    paused: bool,
+
+    /// Shown levels, in the same order as [`LEVELS`]
+    levels: [bool; 5],
+    search: String,
+    /// All seen targets; value is true if target is shown
+    targets: BTreeMap<String, bool>,
+
+    /// Log filter being edited
+    filter: Option<String>,
+    filter_error: Option<String>,
+
+    /// Errors received while window was closed
+    unseen_errors: usize,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self {
+            open: false,
+            docked: false,
+            lines: default(),
+            held: default(),
+            paused: false,
+            levels: [true; 5],
+            search: default(),
+            targets: default(),
+            filter: None,
+            filter_error: None,
+            unseen_errors: 0,
+        }
     }
-    .show(egui_ctx.ctx_mut(), |ui| {
-        // update window size
-        {
-            // TODO: size is probably not entirely correct (esp. considering FONT_SIZE_INCREASE),
-            // plus message can be (and should be!) wrapped, taking two or more rows,
-            // while this expects strictly row per message
+}
 
-            let style = ui.style();
-            let font_size = style.text_styles.get(&egui::TextStyle::Body).unwrap().size;
-            let font_size = font_size + FONT_SIZE_INCREASE;
+impl LogViewer {
+    fn add(&mut self, message: LogMessage) {
+        self.targets.entry(message.target.clone()).or_insert(true);
 
-            let window_height = ui.available_size().y;
-            let max_messages = (window_height / font_size) as usize;
+        if self.paused {
+            if self.held.len() >= MAX_LINES {
+                self.held.pop_front();
+            }
+            self.held.push_back(message);
+            return;
+        }
 
-            if data.messages.len() != max_messages {
-                data.messages.clear();
-                data.messages.resize(max_messages, default());
+        if self.lines.len() >= MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(message);
+    }
 
-                data.next = 0;
+    fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        if !paused {
+            for message in std::mem::take(&mut self.held) {
+                self.add(message);
             }
         }
+    }
 
-        // add new messages
-        {
-            let data = &mut *data;
-            'evloop: for event in messages.drain() {
-                if !settings.log.show_all {
-                    if !match event.level {
-                        Level::TRACE | Level::DEBUG | Level::INFO | Level::WARN => false,
-                        Level::ERROR => !settings.log.hide_errors,
-                    } {
-                        continue;
-                    }
-                }
+    fn is_shown(&self, message: &LogMessage, search: &str) -> bool {
+        let level = LEVELS.iter().position(|level| *level == message.level);
+        level.map(|index| self.levels[index]).unwrap_or(true)
+            && self.targets.get(&message.target).copied().unwrap_or(true)
+            && (search.is_empty() || message.text.to_lowercase().contains(search))
+    }
+}
 
-                for ignored in IGNORED_MESSAGES {
-                    if event.text.starts_with(ignored) {
-                        continue 'evloop;
-                    }
-                }
+fn receive_messages(mut messages: EventReader<LogMessage>, mut viewer: ResMut<LogViewer>) {
+    for message in messages.read() {
+        if message.level == Level::ERROR && !viewer.open {
+            viewer.unseen_errors += 1;
+        }
+        viewer.add(message.clone());
+    }
+}
 
-                for line in event.text.split('\n') {
-                    let event = LogMessage {
-                        level: event.level,
-                        text: format!("LOG: {line}"),
-                    };
+fn toggle_log_window(
+    actions: Res<ActionState<AppActions>>,
+    mut console_commands: EventReader<ConsoleCommand>,
+    mut viewer: ResMut<LogViewer>,
+) {
+    let console = console_commands
+        .read()
+        .filter(|command| command.is("log"))
+        .count();
 
-                    data.messages[data.next] = (Some(event), time.elapsed());
-                    data.next = (data.next + 1) % data.messages.len();
-                }
+    if actions.just_pressed(AppActions::LogWindow) || console % 2 == 1 {
+        viewer.open = !viewer.open;
+        viewer.unseen_errors = 0;
+    }
+}
+
+fn draw_log_window(
+    mut egui_ctx: EguiContexts,
+    mut viewer: ResMut<LogViewer>,
+    mut settings: ResMut<AppSettings>,
+    log_filter: Res<LogFilter>,
+) {
+    if !viewer.open {
+        return;
+    }
+
+    let viewer = &mut *viewer;
+    let ctx = egui_ctx.ctx_mut();
+    let docked = viewer.docked;
+    let mut open = true;
+
+    let mut contents = |ui: &mut egui::Ui| {
+        ui.horizontal(|ui| {
+            for (index, level) in LEVELS.iter().enumerate() {
+                let text = egui::RichText::new(level.as_str()).color(level_color(*level).to_egui());
+                ui.checkbox(&mut viewer.levels[index], text);
             }
-            data.has_any = true;
-        }
 
-        // remove old messages & update state
-        {
-            let data = &mut *data;
-            data.has_any = false;
-            for (message, received_at) in data.messages.iter_mut() {
-                if message.is_some() {
-                    let passed = time.elapsed().saturating_sub(*received_at);
-                    if passed.as_secs_f32() < DURATION {
-                        data.has_any = true;
-                    } else {
-                        *message = None;
+            ui.separator();
+
+            let mut paused = viewer.paused;
+            let pause_text = match viewer.held.is_empty() {
+                true => "Pause".to_string(),
+                false => format!("Pause ({} new)", viewer.held.len()),
+            };
+            if ui.checkbox(&mut paused, pause_text).changed() {
+                viewer.set_paused(paused);
+            }
+            if ui.button("Clear").clicked() {
+                viewer.lines.clear();
+                viewer.held.clear();
+            }
+            ui.checkbox(&mut viewer.docked, "Dock");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut viewer.search);
+
+            ui.menu_button("Targets", |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(300.)
+                    .show(ui, |ui| {
+                        for (target, shown) in viewer.targets.iter_mut() {
+                            ui.checkbox(shown, target.as_str());
+                        }
+                    });
+            });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Filter:").on_hover_text(format!(
+                "Directives added to \"{}\", e.g. \"bevyjam4=debug\"",
+                log_filter.base()
+            ));
+
+            let filter = viewer
+                .filter
+                .get_or_insert_with(|| log_filter.extra().to_string());
+            let response = ui.text_edit_singleline(filter);
+
+            let apply = ui.button("Apply").clicked()
+                || (response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)));
+            if apply {
+                let filter = filter.trim().to_string();
+                viewer.filter_error = match log_filter.check(&filter) {
+                    Ok(()) => {
+                        settings.log.filter = filter;
+                        None
                     }
-                }
+                    Err(error) => Some(error),
+                };
             }
-            if !data.has_any {
-                data.next = 0;
+
+            if let Some(error) = &viewer.filter_error {
+                ui.colored_label(Color::RED.to_egui(), error);
             }
-        }
+        });
+
+        ui.separator();
 
-        // draw messages
-        {
-            let newest_message = data
-                .next
-                .checked_sub(1)
-                .unwrap_or(data.messages.len().saturating_sub(1));
-
-            let last_index = data
-                .messages
-                .iter()
-                .enumerate()
-                .rev()
-                .find_map(|(i, message)| message.0.as_ref().map(|_| i))
-                .unwrap_or_default();
-
-            for (i, (message, received_at)) in data.messages.iter().enumerate() {
-                if let Some(message) = message {
-                    let t = time.elapsed().saturating_sub(*received_at).as_secs_f32() / DURATION;
-                    let alpha = if t < FADE_START {
-                        1.
-                    } else {
-                        map_linear_range(t, FADE_START, DURATION, 1., 0., true)
-                    };
-
-                    let color = level_color(message.level).with_a(alpha);
-
-                    let background_color = if i == newest_message {
-                        invert_color(color).with_a(background_color.a())
-                    } else {
-                        background_color
-                    };
+        let search = viewer.search.to_lowercase();
+        let shown: Vec<_> = viewer
+            .lines
+            .iter()
+            .filter(|message| viewer.is_shown(message, &search))
+            .collect();
 
+        let row_height = ui.text_style_height(&egui::TextStyle::Small);
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .stick_to_bottom(!viewer.paused)
+            .show_rows(ui, row_height, shown.len(), |ui, rows| {
+                for message in &shown[rows] {
                     ui.label(
-                        egui::RichText::new(&message.text)
+                        egui::RichText::new(format!("{}: {}", message.level, message.text))
                             .small()
-                            .color(color.to_egui())
-                            .background_color(background_color.to_egui()),
+                            .color(level_color(message.level).to_egui()),
                     );
-                } else {
-                    if i < last_index {
-                        ui.small(" ");
-                    } else {
-                        break;
-                    }
                 }
-            }
-        }
+            });
+    };
+
+    if docked {
+        egui::TopBottomPanel::bottom("log_window")
+            .resizable(true)
+            .default_height(250.)
+            .show(ctx, |ui| contents(ui));
+    } else {
+        egui::Window::new("Log")
+            .open(&mut open)
+            .default_size([600., 300.])
+            .show(ctx, |ui| contents(ui));
+    }
+
+    if !open {
+        viewer.open = false;
+    }
+}
+
+/// Small notification, so errors aren't missed while window is closed
+fn notify_errors(
+    mut egui_ctx: EguiContexts,
+    viewer: Res<LogViewer>,
+    settings: Res<AppSettings>,
+    prompt: ActionPrompt<AppActions>,
+) {
+    if viewer.open || viewer.unseen_errors == 0 || settings.log.hide_errors {
+        return;
+    }
+
+    EguiPopup {
+        name: "notify_errors",
+        background: false,
+        anchor: egui::Align2::LEFT_TOP,
+        order: egui::Order::Tooltip,
+        interactable: false,
+        ..default()
+    }
+    .show(egui_ctx.ctx_mut(), |ui| {
+        ui.label(
+            egui::RichText::new(format!(
+                "{} new errors - press {} to open log",
+                viewer.unseen_errors,
+                prompt.get(AppActions::LogWindow)
+            ))
+            .small()
+            .color(Color::RED.to_egui())
+            .background_color(invert_color(Color::RED).with_a(0.7).to_egui()),
+        );
     });
 }
@@ -1,4 +1,5 @@
 use super::states::MenuState;
+use crate::app::localization::Localization;
 use crate::app::scores::Scores;
 use crate::gameplay::master::game_states::GameCommand;
 use crate::gameplay::master::game_states::GameRunning;
@@ -33,84 +34,69 @@ impl Plugin for MainPlugin {
 fn draw_main_menu(
     mut egui_ctx: EguiContexts,
     mut next_state: ResMut<NextState<MenuState>>,
-    game_running: Res<State<GameRunning>>,
     mut game_commands: EventWriter<GameCommand>,
     scores: Res<Scores>,
     mut exit: EventWriter<AppExit>,
     levels: Res<LevelList>,
+    loc: Res<Localization>,
 ) {
     EguiPopup {
         name: "draw_main_menu",
         ..default()
     }
     .show(egui_ctx.ctx_mut(), |ui| {
-        match game_running.get() {
-            GameRunning::Yes => {
-                if ui.button("Back to game").clicked() {
-                    next_state.set(MenuState::None);
-                }
-                if ui.button("Restart").clicked() {
-                    next_state.set(MenuState::None);
-                    game_commands.send(GameCommand::Respawn);
-                }
-            }
-            GameRunning::No => {
-                if let Some(level) = &scores.last_level {
-                    if ui
-                        .button(format!("Continue: {}", levels.name(&level.id)))
-                        .clicked()
-                    {
-                        next_state.set(MenuState::None);
-                        game_commands.send(GameCommand::Start {
-                            level_id: level.id.clone(),
-                        });
-                    }
-                }
-
-                if ui.button("New game").clicked() {
-                    next_state.set(MenuState::Intro);
-                    game_commands.send(GameCommand::Start {
-                        level_id: levels.first(),
-                    });
-                }
-
-                if ui.button("Select level").clicked() {
-                    next_state.set(MenuState::LevelSelect);
-                }
+        if let Some(level) = &scores.last_level {
+            let name = levels.name(&level.id, &loc);
+            if ui
+                .button(loc.format("menu.continue", &[("level", &name)]))
+                .clicked()
+            {
+                next_state.set(MenuState::None);
+                game_commands.send(GameCommand::Continue {
+                    level_id: level.id.clone(),
+                });
             }
         }
 
-        ui.label("");
-
-        if ui.button("Settings").clicked() {
-            next_state.set(MenuState::Settings);
+        if ui.button(loc.get("menu.new_game")).clicked() {
+            next_state.set(MenuState::Intro);
+            game_commands.send(GameCommand::Start {
+                level_id: levels.first(),
+            });
         }
 
-        #[cfg(not(target_arch = "wasm32"))] // levels can't be saved on wasm
-        if game_running.get().is_yes() {
-            if ui.button("Edit level").clicked() {
-                next_state.set(MenuState::LevelEditor);
-            }
+        if ui.button(loc.get("menu.select_level")).clicked() {
+            next_state.set(MenuState::LevelSelect);
         }
 
-        if game_running.get().is_yes() {
-            ui.label("");
+        ui.label("");
 
-            if ui.button("Exit to main menu").clicked() {
-                game_commands.send(GameCommand::Exit);
-            }
-        } else {
-            #[cfg(not(target_arch = "wasm32"))]
-            ui.label("");
+        if ui.button(upgrades_label(&scores, &loc)).clicked() {
+            next_state.set(MenuState::Upgrades);
         }
 
+        if ui.button(loc.get("menu.settings")).clicked() {
+            next_state.set(MenuState::Settings);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.label("");
+
         #[cfg(not(target_arch = "wasm32"))] // there is no point in exiting in wasm
-        if ui.button("Exit to desktop").clicked() {
+        if ui.button(loc.get("menu.exit_to_desktop")).clicked() {
             exit.send_default();
         }
     });
 }
 
+/// Shows number of available points
+pub(super) fn upgrades_label(scores: &Scores, loc: &Localization) -> String {
+    match scores.upgrades.points {
+        0 => loc.get("menu.upgrades"),
+        points => loc.format("menu.upgrades_available", &[("points", &points)]),
+    }
+}
+
 fn draw_menu_background(mut egui_ctx: EguiContexts) {
     let alpha = 0.7;
 
@@ -138,9 +124,14 @@ fn draw_menu_background(mut egui_ctx: EguiContexts) {
 
 fn crab(mut egui_ctx: EguiContexts, state: Res<State<MenuState>>, mut hovered: Local<bool>) {
     let show = match state.get() {
-        MenuState::Startup | MenuState::MainMenu | MenuState::LevelSelect | MenuState::Settings => {
-            true
-        }
+        MenuState::Startup
+        | MenuState::MainMenu
+        | MenuState::Pause
+        | MenuState::LevelSelect
+        | MenuState::Settings
+        | MenuState::Controls
+        | MenuState::Upgrades
+        | MenuState::MessageHistory => true,
         MenuState::None
         | MenuState::LevelEditor
         | MenuState::ModalMessage
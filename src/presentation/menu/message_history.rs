@@ -0,0 +1,110 @@
+use super::messages::MessageHistory;
+use super::messages::ReplayMessage;
+use super::states::CloseMenu;
+use super::states::MenuState;
+use crate::app::localization::Localization;
+use crate::gameplay::master::level_progress::LevelList;
+use crate::presentation::MessageCategory;
+use crate::utils::bevy_egui::*;
+use bevy::prelude::*;
+
+/// List of already shown messages, available from the game menu
+pub struct MessageHistoryPlugin;
+
+impl Plugin for MessageHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HistoryFilter>().add_systems(
+            Update,
+            draw_message_history.run_if(in_state(MenuState::MessageHistory)),
+        );
+    }
+}
+
+/// Shown categories, in the same order as [`MessageCategory::all`]
+#[derive(Resource)]
+struct HistoryFilter([bool; 4]);
+
+impl Default for HistoryFilter {
+    fn default() -> Self {
+        Self([true; 4])
+    }
+}
+
+fn draw_message_history(
+    mut egui_ctx: EguiContexts,
+    mut close_menu: EventWriter<CloseMenu>,
+    history: Res<MessageHistory>,
+    mut filter: ResMut<HistoryFilter>,
+    levels: Res<LevelList>,
+    mut replays: EventWriter<ReplayMessage>,
+    loc: Res<Localization>,
+) {
+    EguiPopup {
+        name: "draw_message_history",
+        ..default()
+    }
+    .show(egui_ctx.ctx_mut(), |ui| {
+        let screen = ui.ctx().screen_rect();
+
+        ui.heading(loc.get("history.title"));
+
+        ui.horizontal(|ui| {
+            for (index, category) in MessageCategory::all().into_iter().enumerate() {
+                let text =
+                    egui::RichText::new(category.name(&loc)).color(category.color().to_egui());
+                ui.checkbox(&mut filter.0[index], text);
+            }
+        });
+        ui.label("");
+
+        let shown: Vec<_> = history
+            .0
+            .iter()
+            .rev()
+            .filter(|entry| {
+                let index = MessageCategory::all()
+                    .iter()
+                    .position(|category| *category == entry.message.category);
+                index.map(|index| filter.0[index]).unwrap_or(true)
+            })
+            .collect();
+
+        if shown.is_empty() {
+            ui.label(loc.get("history.empty"));
+        }
+
+        egui::ScrollArea::vertical()
+            .max_height(screen.height() * 0.6)
+            .show(ui, |ui| {
+                for (index, entry) in shown.into_iter().enumerate() {
+                    let message = &entry.message;
+                    let category = message.category.name(&loc);
+                    let title = match message.header.is_empty() {
+                        true => category,
+                        false => format!("{category}: {}", message.header),
+                    };
+
+                    egui::CollapsingHeader::new(
+                        egui::RichText::new(title).color(message.category.color().to_egui()),
+                    )
+                    .id_source(index)
+                    .default_open(index == 0)
+                    .show(ui, |ui| {
+                        if !entry.level_id.is_empty() {
+                            ui.small(levels.name(&entry.level_id, &loc));
+                        }
+                        ui.label(&message.text);
+
+                        if ui.button(loc.get("history.show_again")).clicked() {
+                            replays.send(ReplayMessage(message.clone()));
+                        }
+                    });
+                }
+            });
+
+        ui.label("");
+        if ui.button(loc.get("menu.back_to_menu")).clicked() {
+            close_menu.send_default();
+        }
+    });
+}
@@ -1,8 +1,9 @@
-#![allow(unused)] // TODO: remove
-
 use super::states::CloseMenu;
 use super::states::MenuState;
 use super::ui_const::UiConst;
+use crate::app::localization::Localization;
+use crate::gameplay::master::level::current::CurrentLevel;
+use crate::gameplay::master::level::current::LevelCommand;
 use crate::utils::bevy_egui::*;
 use crate::utils::math_algorithms::lerp;
 use crate::utils::misc_utils::DurationDivF32 as _;
@@ -18,6 +19,7 @@ pub struct Message {
     pub header: String,
     pub text: String,
     pub ty: MessageType,
+    pub category: MessageCategory,
 }
 
 impl Message {
@@ -26,16 +28,40 @@ impl Message {
             header: header.into(),
             text: text.into(),
             ty: MessageType::Notification,
+            category: MessageCategory::Info,
         }
     }
 
+    pub fn with_category(mut self, category: MessageCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// Level-specific messages are cancelled if level is unloaded before they are sent
     pub fn delay(self, by: Duration, virtual_time: bool) -> DelayedMessage {
         DelayedMessage {
+            cancel_on_unload: self.category.level_specific(),
             message: self,
             by,
             virtual_time,
         }
     }
+
+    fn same_as(&self, other: &Message) -> bool {
+        self.category == other.category && self.header == other.header && self.text == other.text
+    }
+
+    /// How long message should be shown - enough to read it without hurry.
+    /// Doesn't include fade-in and fade-out durations.
+    fn duration(&self) -> Duration {
+        let time_to_notice = 1.5;
+        let seconds_per_word = 0.35;
+
+        let words = self.header.split_whitespace().count() + self.text.split_whitespace().count();
+        let (min, max) = self.category.duration_limits();
+        let seconds = (time_to_notice + seconds_per_word * words as f32).clamp(min, max);
+        Duration::from_secs_f32(seconds)
+    }
 }
 
 /// Look and behavior
@@ -50,16 +76,6 @@ pub enum MessageType {
 }
 
 impl MessageType {
-    // TODO: this is all kinds of wrong, but works for now
-    /// How long message should be shown.
-    /// Doesn't include fade-in and fade-out durations.
-    fn duration(&self, text: &str) -> Duration {
-        let seconds_per_letter = 0.020;
-        let seconds = seconds_per_letter * text.len() as f32;
-        let seconds = seconds.clamp(3., 7.);
-        Duration::from_secs_f32(seconds)
-    }
-
     fn modal(&self) -> bool {
         match self {
             MessageType::ModalNotification => true,
@@ -68,12 +84,100 @@ impl MessageType {
     }
 }
 
+/// What message is about. Defines priority and how messages stack:
+///
+/// - only one message is shown at a time; higher priority ones first, otherwise in order of arrival;
+/// - message interrupts shown message with lower priority, which is shown again later;
+/// - copies of shown or queued messages are ignored;
+/// - warnings are discarded if they weren't shown in [`WARNING_EXPIRATION`];
+/// - tutorials and objectives are discarded when level changes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageCategory {
+    Info,
+    Tutorial,
+    Objective,
+    Warning,
+}
+
+impl MessageCategory {
+    pub fn all() -> [Self; 4] {
+        [Self::Info, Self::Tutorial, Self::Objective, Self::Warning]
+    }
+
+    pub fn name(self, loc: &Localization) -> String {
+        loc.get(match self {
+            Self::Info => "message.info",
+            Self::Tutorial => "message.tutorial",
+            Self::Objective => "message.objective",
+            Self::Warning => "message.warning",
+        })
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Info => Color::WHITE,
+            Self::Tutorial => Color::rgb(0.7, 0.9, 1.),
+            Self::Objective => Color::rgb(1., 0.9, 0.5),
+            Self::Warning => Color::rgb(1., 0.5, 0.3),
+        }
+    }
+
+    fn priority(self) -> u8 {
+        match self {
+            Self::Tutorial => 0,
+            Self::Info => 1,
+            Self::Objective => 2,
+            Self::Warning => 3,
+        }
+    }
+
+    /// Makes no sense outside of the level in which it was sent
+    fn level_specific(self) -> bool {
+        match self {
+            Self::Tutorial | Self::Objective => true,
+            Self::Info | Self::Warning => false,
+        }
+    }
+
+    /// Min and max duration, in seconds
+    fn duration_limits(self) -> (f32, f32) {
+        match self {
+            Self::Info => (3., 8.),
+            Self::Tutorial | Self::Objective => (4., 12.),
+            Self::Warning => (2.5, 6.),
+        }
+    }
+}
+
+/// Warnings are about current situation, so they're useless if shown too late
+const WARNING_EXPIRATION: Duration = Duration::from_secs(10);
+
+/// Max number of messages kept in [`MessageHistory`]
+const MAX_HISTORY: usize = 100;
+
 /// Sends message after specified amount of time passes
 #[derive(Event)]
 pub struct DelayedMessage {
     pub message: Message,
     pub by: Duration,
     pub virtual_time: bool,
+
+    /// Discard message if level is changed or unloaded before it's sent
+    pub cancel_on_unload: bool,
+}
+
+/// Show message from [`MessageHistory`] again, without adding a copy to it
+#[derive(Event)]
+pub(super) struct ReplayMessage(pub Message);
+
+/// Shown messages, oldest first
+#[derive(Resource, Default)]
+pub(super) struct MessageHistory(pub VecDeque<HistoryEntry>);
+
+pub(super) struct HistoryEntry {
+    pub message: Message,
+    /// ID of the level in which message was shown
+    pub level_id: String,
 }
 
 pub struct MessagesPlugin;
@@ -82,11 +186,16 @@ impl Plugin for MessagesPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<Message>()
             .add_event::<DelayedMessage>()
+            .add_event::<ReplayMessage>()
             .init_resource::<MessageData>()
+            .init_resource::<PendingMessages>()
+            .init_resource::<MessageHistory>()
             .add_systems(
                 PostUpdate,
                 (
+                    cancel_level_messages.run_if(on_event::<LevelCommand>()),
                     delayed_message,
+                    replay_messages.run_if(on_event::<ReplayMessage>()),
                     draw_and_update_messages.before(EguiSet::ProcessOutput),
                 )
                     .chain(),
@@ -95,11 +204,30 @@ impl Plugin for MessagesPlugin {
     }
 }
 
+/// Replayed messages are already in history, so they're marked as seen
+fn replay_messages(
+    mut replays: EventReader<ReplayMessage>,
+    mut data: ResMut<MessageData>,
+    time: Res<Time<Real>>,
+) {
+    for ReplayMessage(message) in replays.read() {
+        data.add(QueuedMessage {
+            message: message.clone(),
+            queued_at: time.elapsed(),
+            seen: true,
+        });
+    }
+}
+
+/// Delayed messages and time at which they should be sent
+#[derive(Resource, Default)]
+struct PendingMessages(Vec<(Duration, DelayedMessage)>);
+
 fn delayed_message(
     mut delayed: ResMut<Events<DelayedMessage>>,
     time: Res<Time<Real>>,
     time_virtual: Res<Time>,
-    mut data: Local<Vec<(Duration, Message, bool)>>,
+    mut pending: ResMut<PendingMessages>,
     mut messages: EventWriter<Message>,
 ) {
     let time_elapsed = |virtuall| match virtuall {
@@ -107,23 +235,47 @@ fn delayed_message(
         false => time.elapsed(),
     };
 
-    data.extend(delayed.drain().map(|delay| {
-        (
-            time_elapsed(delay.virtual_time) + delay.by,
-            delay.message,
-            delay.virtual_time,
-        )
-    }));
+    pending.0.extend(
+        delayed
+            .drain()
+            .map(|delay| (time_elapsed(delay.virtual_time) + delay.by, delay)),
+    );
 
-    data.retain(|(after, message, virtual_time)| {
-        let retain = time_elapsed(*virtual_time) < *after;
+    pending.0.retain(|(after, delay)| {
+        let retain = time_elapsed(delay.virtual_time) < *after;
         if !retain {
-            messages.send(message.clone());
+            messages.send(delay.message.clone());
         }
         retain
     });
 }
 
+/// Tutorial for the previous level shouldn't be shown in the next one
+fn cancel_level_messages(
+    mut level_commands: EventReader<LevelCommand>,
+    mut pending: ResMut<PendingMessages>,
+    mut data: ResMut<MessageData>,
+) {
+    let unloaded = level_commands
+        .read()
+        .any(|command| matches!(command, LevelCommand::Load(_) | LevelCommand::Unload));
+    if !unloaded {
+        return;
+    }
+
+    pending.0.retain(|(_, delay)| !delay.cancel_on_unload);
+
+    data.queue
+        .retain(|entry| !entry.message.category.level_specific());
+
+    let current_level_specific = data.current.as_ref().is_some_and(|(entry, _)| {
+        entry.message.category.level_specific() && !entry.message.ty.modal()
+    });
+    if current_level_specific {
+        data.current = None;
+    }
+}
+
 fn fade_in_duration() -> Duration {
     Duration::from_secs_f32(0.500)
 }
@@ -132,23 +284,84 @@ fn fade_out_duration() -> Duration {
     Duration::from_secs_f32(0.600)
 }
 
+struct QueuedMessage {
+    message: Message,
+    /// When message was received
+    queued_at: Duration,
+    /// Already added to history
+    seen: bool,
+}
+
 #[derive(Resource, Default)]
 struct MessageData {
-    current_started_at: Option<Duration>,
-    queue: VecDeque<Message>,
+    /// Shown message and when it was shown
+    current: Option<(QueuedMessage, Duration)>,
+    /// Ordered by priority
+    queue: VecDeque<QueuedMessage>,
 }
 
 impl MessageData {
-    fn pop(&mut self) {
-        self.current_started_at = None;
-        self.queue.pop_front();
+    fn add(&mut self, entry: QueuedMessage) {
+        let duplicate = self
+            .current
+            .iter()
+            .map(|(current, _)| current)
+            .chain(self.queue.iter())
+            .any(|queued| queued.message.same_as(&entry.message));
+        if duplicate {
+            return;
+        }
+
+        // after all messages with the same or higher priority
+        let priority = entry.message.category.priority();
+        let index = self
+            .queue
+            .iter()
+            .position(|queued| queued.message.category.priority() < priority)
+            .unwrap_or(self.queue.len());
+        self.queue.insert(index, entry);
+    }
+
+    /// Apply stacking rules. Returns true if new message is shown.
+    fn update(&mut self, now: Duration) -> bool {
+        self.queue.retain(|queued| {
+            queued.message.category != MessageCategory::Warning
+                || now.saturating_sub(queued.queued_at) < WARNING_EXPIRATION
+        });
+
+        if let Some((current, started_at)) = &self.current {
+            let next_priority = self.queue.front().map(|next| next.message.category.priority());
+            let interrupted = !current.message.ty.modal()
+                && next_priority > Some(current.message.category.priority());
+
+            if interrupted {
+                let passed = now.saturating_sub(*started_at);
+                let (current, _) = self.current.take().unwrap();
+
+                // show it again only if it wasn't read completely
+                if passed < fade_in_duration() + current.message.duration() {
+                    self.add(current);
+                }
+            }
+        }
+
+        if self.current.is_some() {
+            return false;
+        }
+        match self.queue.pop_front() {
+            Some(next) => {
+                self.current = Some((next, now));
+                true
+            }
+            None => false,
+        }
     }
 }
 
 fn pop_modal_message(mut data: ResMut<MessageData>) {
-    if let Some(message) = data.queue.front() {
-        if message.ty.modal() {
-            data.pop();
+    if let Some((current, _)) = &data.current {
+        if current.message.ty.modal() {
+            data.current = None;
         }
     }
 }
@@ -156,12 +369,15 @@ fn pop_modal_message(mut data: ResMut<MessageData>) {
 fn draw_and_update_messages(
     mut messages: ResMut<Events<Message>>,
     mut data: ResMut<MessageData>,
+    mut history: ResMut<MessageHistory>,
     time: Res<Time<Real>>,
     mut egui_ctx: EguiContexts,
     ui_const: UiConst,
     mut close_menu: EventWriter<CloseMenu>,
     mut menu_state: ResMut<NextState<MenuState>>,
     primary_window: Query<(), With<PrimaryWindow>>,
+    level: Res<CurrentLevel>,
+    loc: Res<Localization>,
 ) {
     let ui_const = ui_const.scale();
     let margin = 20. * ui_const;
@@ -177,110 +393,128 @@ fn draw_and_update_messages(
     }
 
     let data = &mut *data;
+    let now = time.elapsed();
+
+    for message in messages.drain() {
+        data.add(QueuedMessage {
+            message,
+            queued_at: now,
+            seen: false,
+        });
+    }
 
-    data.queue.extend(messages.drain());
+    if data.update(now) {
+        let Some((current, _)) = &mut data.current else { return; };
 
-    //
-
-    if let Some(message) = data.queue.front() {
-        if data.current_started_at.is_none() && message.ty.modal() {
+        if current.message.ty.modal() {
             menu_state.set(MenuState::ModalMessage);
         }
 
-        let started_at = *data.current_started_at.get_or_insert(time.elapsed());
-        let passed = time.elapsed().saturating_sub(started_at);
+        if !current.seen {
+            current.seen = true;
+            if history.0.len() >= MAX_HISTORY {
+                history.0.pop_front();
+            }
+            history.0.push_back(HistoryEntry {
+                message: current.message.clone(),
+                level_id: level.id.clone(),
+            });
+        }
+    }
+
+    //
 
-        let text_duration = message.ty.duration(&message.text);
-        let total_duration = text_duration + fade_in_duration() + fade_out_duration();
+    let Some((current, started_at)) = &data.current else { return; };
+    let message = &current.message;
+    let passed = now.saturating_sub(*started_at);
 
-        if passed >= total_duration && !message.ty.modal() {
-            data.pop();
+    let text_duration = message.duration();
+    let total_duration = text_duration + fade_in_duration() + fade_out_duration();
+
+    if passed >= total_duration && !message.ty.modal() {
+        data.current = None;
+    } else {
+        let bg_alpha = 0.7;
+        let bg_color_start = Color::rgb(0.3, 0.6, 0.6);
+        let bg_color_main = Color::BLACK;
+
+        let text_color = message.category.color();
+
+        let fade_in;
+        let alpha;
+
+        if passed < fade_in_duration() {
+            fade_in = passed.div_dur_f32(fade_in_duration());
+            alpha = 1.;
+        } else if passed < fade_in_duration() + text_duration {
+            fade_in = 1.;
+            alpha = 1.;
         } else {
-            let bg_alpha = 0.7;
-            let bg_color_start = Color::rgb(0.3, 0.6, 0.6);
-            let bg_color_main = Color::BLACK;
-
-            let text_color = match message.ty {
-                MessageType::Notification => Color::WHITE,
-                MessageType::ModalNotification => Color::WHITE,
-            };
-
-            let fade_in;
-            let alpha;
-
-            if passed < fade_in_duration() {
-                fade_in = passed.div_dur_f32(fade_in_duration());
-                alpha = 1.;
-            } else if passed < fade_in_duration() + text_duration {
-                fade_in = 1.;
-                alpha = 1.;
-            } else {
-                fade_in = 1.;
-                alpha = 1.
-                    - passed
-                        .saturating_sub(fade_in_duration() + text_duration)
-                        .div_dur_f32(fade_out_duration());
-            };
-
-            if message.ty.modal() {
-                EguiPopup {
-                    name: "draw_messages",
-                    anchor: egui::Align2::CENTER_CENTER,
-                    order: egui::Order::Foreground,
-                    ..default()
-                }
-                .show(egui_ctx.ctx_mut(), |ui| {
-                    let style = ui.style_mut();
-                    style.spacing.window_margin = egui::Margin::same(margin);
-                    style.visuals.window_fill = lerp(bg_color_start, bg_color_main, fade_in)
-                        .with_a(alpha * bg_alpha)
-                        .to_egui();
-                    style.visuals.window_stroke = egui::Stroke::NONE; // no border
-
-                    egui::Frame::popup(style).show(ui, |ui| {
-                        if message.header.is_empty() {
-                            ui.label(egui::RichText::new(&message.text).strong().size(text_size));
-                        } else {
-                            ui.label(egui::RichText::new(&message.header).heading().strong());
-                            ui.label(&message.text);
-                        }
-
-                        if ui.button("OK").clicked() {
-                            close_menu.send_default();
-                        }
-                    });
-                });
-            } else {
-                EguiPopup {
-                    name: "draw_messages",
-                    anchor: egui::Align2::CENTER_BOTTOM,
-                    offset: popup_offset,
-                    order: egui::Order::Foreground,
-                    interactable: false,
-                    background: false,
-                    ..default()
-                }
-                .show(egui_ctx.ctx_mut(), |ui| {
-                    let style = ui.style_mut();
-                    style.spacing.window_margin = egui::Margin::same(margin);
-                    style.visuals.window_fill = lerp(bg_color_start, bg_color_main, fade_in)
-                        .with_a(alpha * bg_alpha)
-                        .to_egui();
-                    style.visuals.window_stroke = egui::Stroke::NONE; // no border
-
-                    egui::Frame::popup(style).show(ui, |ui| {
-                        ui.visuals_mut().override_text_color =
-                            text_color.with_a(alpha).to_egui().into();
-
-                        if message.header.is_empty() {
-                            ui.label(egui::RichText::new(&message.text).strong().size(text_size));
-                        } else {
-                            ui.label(egui::RichText::new(&message.header).heading().strong());
-                            ui.label(&message.text);
-                        }
-                    });
+            fade_in = 1.;
+            alpha = 1.
+                - passed
+                    .saturating_sub(fade_in_duration() + text_duration)
+                    .div_dur_f32(fade_out_duration());
+        };
+
+        if message.ty.modal() {
+            EguiPopup {
+                name: "draw_messages",
+                anchor: egui::Align2::CENTER_CENTER,
+                order: egui::Order::Foreground,
+                ..default()
+            }
+            .show(egui_ctx.ctx_mut(), |ui| {
+                let style = ui.style_mut();
+                style.spacing.window_margin = egui::Margin::same(margin);
+                style.visuals.window_fill = lerp(bg_color_start, bg_color_main, fade_in)
+                    .with_a(alpha * bg_alpha)
+                    .to_egui();
+                style.visuals.window_stroke = egui::Stroke::NONE; // no border
+
+                egui::Frame::popup(style).show(ui, |ui| {
+                    if message.header.is_empty() {
+                        ui.label(egui::RichText::new(&message.text).strong().size(text_size));
+                    } else {
+                        ui.label(egui::RichText::new(&message.header).heading().strong());
+                        ui.label(&message.text);
+                    }
+
+                    if ui.button(loc.get("message.ok")).clicked() {
+                        close_menu.send_default();
+                    }
                 });
+            });
+        } else {
+            EguiPopup {
+                name: "draw_messages",
+                anchor: egui::Align2::CENTER_BOTTOM,
+                offset: popup_offset,
+                order: egui::Order::Foreground,
+                interactable: false,
+                background: false,
+                ..default()
             }
+            .show(egui_ctx.ctx_mut(), |ui| {
+                let style = ui.style_mut();
+                style.spacing.window_margin = egui::Margin::same(margin);
+                style.visuals.window_fill = lerp(bg_color_start, bg_color_main, fade_in)
+                    .with_a(alpha * bg_alpha)
+                    .to_egui();
+                style.visuals.window_stroke = egui::Stroke::NONE; // no border
+
+                egui::Frame::popup(style).show(ui, |ui| {
+                    ui.visuals_mut().override_text_color =
+                        text_color.with_a(alpha).to_egui().into();
+
+                    if message.header.is_empty() {
+                        ui.label(egui::RichText::new(&message.text).strong().size(text_size));
+                    } else {
+                        ui.label(egui::RichText::new(&message.header).heading().strong());
+                        ui.label(&message.text);
+                    }
+                });
+            });
         }
     }
 }
@@ -1,19 +1,25 @@
 use bevy::prelude::*;
 
+mod console;
 mod debug;
+mod gamepad;
 mod in_game;
 mod inoutro;
+mod inspector;
 mod level_editor;
 mod levels;
 mod log;
 mod main;
+mod message_history;
 mod messages;
+mod pause;
 mod settings;
 mod startup;
 mod states;
 mod ui_const;
+mod upgrades;
 
-pub use messages::{DelayedMessage, Message, MessageType};
+pub use messages::{DelayedMessage, Message, MessageCategory, MessageType};
 pub use ui_const::UiConst;
 
 pub struct MenuPlugin;
@@ -22,16 +28,20 @@ impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             level_editor::LevelEditorPlugin,
+            console::ConsolePlugin,
             debug::DebugPlugin,
+            inspector::InspectorPlugin,
+            gamepad::GamepadPlugin,
             log::LogPlugin,
-            main::MainPlugin,
+            (main::MainPlugin, pause::PausePlugin),
             settings::SettingsPlugin,
             states::StatesPlugin,
             startup::StartupPlugin,
             in_game::HudPlugin,
-            messages::MessagesPlugin,
+            (messages::MessagesPlugin, message_history::MessageHistoryPlugin),
             levels::LevelsPlugin,
             inoutro::InoutroPlugin,
+            upgrades::UpgradesPlugin,
         ));
     }
 }
@@ -0,0 +1,104 @@
+use super::level_editor::EditorEnabled;
+use super::main::upgrades_label;
+use super::states::MenuState;
+use crate::app::localization::Localization;
+use crate::app::scores::Scores;
+use crate::app::settings::AppSettings;
+use crate::gameplay::master::game_states::GameCommand;
+use crate::gameplay::master::game_states::GameRunning;
+use crate::utils::bevy_egui::*;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+
+/// Menu shown over the running game
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                draw_pause_menu.run_if(in_state(MenuState::Pause)),
+                pause_on_focus_loss.run_if(on_event::<WindowFocused>()),
+            ),
+        );
+    }
+}
+
+fn draw_pause_menu(
+    mut egui_ctx: EguiContexts,
+    mut next_state: ResMut<NextState<MenuState>>,
+    mut game_commands: EventWriter<GameCommand>,
+    scores: Res<Scores>,
+    mut exit: EventWriter<AppExit>,
+    loc: Res<Localization>,
+) {
+    EguiPopup {
+        name: "draw_pause_menu",
+        ..default()
+    }
+    .show(egui_ctx.ctx_mut(), |ui| {
+        ui.heading(loc.get("pause.title"));
+        ui.label("");
+
+        if ui.button(loc.get("menu.resume")).clicked() {
+            next_state.set(MenuState::None);
+        }
+        if ui.button(loc.get("menu.restart")).clicked() {
+            next_state.set(MenuState::None);
+            game_commands.send(GameCommand::Respawn);
+        }
+
+        ui.label("");
+
+        if ui.button(upgrades_label(&scores, &loc)).clicked() {
+            next_state.set(MenuState::Upgrades);
+        }
+        if ui.button(loc.get("menu.settings")).clicked() {
+            next_state.set(MenuState::Settings);
+        }
+        if ui.button(loc.get("menu.controls")).clicked() {
+            next_state.set(MenuState::Controls);
+        }
+        if ui.button(loc.get("menu.message_history")).clicked() {
+            next_state.set(MenuState::MessageHistory);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))] // levels can't be saved on wasm
+        if ui.button(loc.get("menu.edit_level")).clicked() {
+            next_state.set(MenuState::LevelEditor);
+        }
+
+        ui.label("");
+
+        // menu state is changed to main menu on exit
+        if ui.button(loc.get("menu.exit_to_main_menu")).clicked() {
+            game_commands.send(GameCommand::Exit);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))] // there is no point in exiting in wasm
+        if ui.button(loc.get("menu.exit_to_desktop")).clicked() {
+            exit.send_default();
+        }
+    });
+}
+
+fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    settings: Res<AppSettings>,
+    state: Res<State<MenuState>>,
+    game_running: Res<State<GameRunning>>,
+    editor: Res<State<EditorEnabled>>,
+    mut next_state: ResMut<NextState<MenuState>>,
+) {
+    let lost_focus = focus_events.read().any(|event| !event.focused);
+    let in_game = *state.get() == MenuState::None
+        && game_running.get().is_yes()
+        && *editor.get() == EditorEnabled::No;
+
+    if lost_focus && in_game && settings.gameplay.pause_on_focus_loss {
+        info!("window lost focus, pausing");
+        next_state.set(MenuState::Pause);
+    }
+}
@@ -1,17 +1,41 @@
+use super::states::CloseMenu;
 use super::states::MenuState;
+use crate::app::actions::input_name;
+use crate::app::actions::ActionInfo;
+use crate::app::actions::AppActions;
+use crate::app::diagnostics::diagnostics_report;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::app::diagnostics::write_report;
+use crate::app::localization::Localization;
+use crate::app::localization::LANGUAGES;
 use crate::app::settings::AppSettings;
+use crate::app::settings::ControlSettings;
+use crate::gameplay::balance::Difficulty;
+use crate::gameplay::physics::PhysicsType;
 use crate::utils::bevy_egui::*;
 use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use leafwing_input_manager::user_input::InputKind;
 
 pub struct SettingsPlugin;
 
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<NewUiScale>()
+            .init_resource::<Rebinding>()
             .add_systems(OnEnter(MenuState::Settings), update_ui_scale)
+            .add_systems(OnExit(MenuState::Settings), cancel_rebinding)
+            .add_systems(OnExit(MenuState::Controls), cancel_rebinding)
             .add_systems(
                 Update,
-                draw_settings_menu.run_if(in_state(MenuState::Settings)),
+                (
+                    capture_binding.run_if(
+                        in_state(MenuState::Settings).or_else(in_state(MenuState::Controls)),
+                    ),
+                    draw_settings_menu.run_if(in_state(MenuState::Settings)),
+                    draw_controls_menu.run_if(in_state(MenuState::Controls)),
+                )
+                    .chain(),
             );
     }
 }
@@ -19,15 +43,44 @@ impl Plugin for SettingsPlugin {
 #[derive(Resource, Default)]
 struct NewUiScale(f32);
 
+/// Action which waits for the input to be bound to it
+#[derive(Resource, Default)]
+struct Rebinding(Option<RebindTarget>);
+
+struct RebindTarget {
+    map: ControlsMap,
+    /// Index of the action
+    action: usize,
+    /// Keys already pressed for [`VirtualDPad`]: up, left, down, right
+    dpad: Option<Vec<KeyCode>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ControlsMap {
+    App,
+    Player,
+    Editor,
+}
+
+impl ControlsMap {
+    /// App actions are always active together with other ones
+    fn active_with(self, other: Self) -> bool {
+        self == other || self == Self::App || other == Self::App
+    }
+}
+
 fn update_ui_scale(settings: Res<AppSettings>, mut new_ui_scale: ResMut<NewUiScale>) {
     new_ui_scale.0 = settings.graphics.ui_scale;
 }
 
 fn draw_settings_menu(
     mut egui_ctx: EguiContexts,
-    mut next_state: ResMut<NextState<MenuState>>,
+    mut close_menu: EventWriter<CloseMenu>,
     mut settings_res: ResMut<AppSettings>,
     mut new_ui_scale: ResMut<NewUiScale>,
+    mut rebinding: ResMut<Rebinding>,
+    mut diagnostics_status: Local<String>,
+    loc: Res<Localization>,
 ) {
     EguiPopup {
         name: "draw_settings_menu",
@@ -46,6 +99,10 @@ fn draw_settings_menu(
                         .checkbox(&mut settings.debug.show_fps, "Show FPS counter")
                         .changed();
 
+                    changed |= ui
+                        .checkbox(&mut settings.debug.show_perf, "Performance overlay")
+                        .changed();
+
                     changed |= ui
                         .checkbox(&mut settings.debug.show_physics, "Debug physics render")
                         .changed();
@@ -60,47 +117,404 @@ fn draw_settings_menu(
                             "On startup: Continue & Level Editor",
                         )
                         .changed();
+
+                    ui.collapsing("Collision matrix", collision_matrix);
                 });
             }
 
+            ui.horizontal(|ui| {
+                ui.label(loc.get("settings.language"));
+                egui::ComboBox::from_id_source("language")
+                    .selected_text(loc.language_name(loc.language()))
+                    .show_ui(ui, |ui| {
+                        for code in LANGUAGES {
+                            if ui
+                                .selectable_label(loc.language() == code, loc.language_name(code))
+                                .clicked()
+                            {
+                                settings.language = code.to_string();
+                                changed = true;
+                            }
+                        }
+                    });
+            });
+
             ui.group(|ui| {
-                ui.strong("Graphics");
+                ui.strong(loc.get("settings.gameplay"));
+
+                ui.horizontal(|ui| {
+                    ui.label(loc.get("settings.difficulty"));
+                    for difficulty in Difficulty::all() {
+                        changed |= ui
+                            .radio_value(
+                                &mut settings.gameplay.difficulty,
+                                difficulty,
+                                difficulty.name(&loc),
+                            )
+                            .changed();
+                    }
+                });
+
+                changed |= ui
+                    .checkbox(
+                        &mut settings.gameplay.pause_on_focus_loss,
+                        loc.get("settings.pause_on_focus_loss"),
+                    )
+                    .changed();
+            });
+
+            ui.group(|ui| {
+                ui.strong(loc.get("settings.graphics"));
 
                 ui.add(
                     egui::Slider::new(&mut new_ui_scale.0, 0.1..=10.)
-                        .text("UI scale")
+                        .text(loc.get("settings.ui_scale"))
                         .clamp_to_range(false)
                         .prefix("x"),
                 );
-                if ui.button("Apply UI scale").clicked() {
+                if ui.button(loc.get("settings.apply_ui_scale")).clicked() {
                     settings.graphics.ui_scale = new_ui_scale.0;
                     changed = true;
                 }
 
                 changed |= ui
-                    .checkbox(&mut settings.graphics.fullscreen, "Fullscreen")
+                    .checkbox(&mut settings.graphics.fullscreen, loc.get("settings.fullscreen"))
+                    .changed();
+
+                changed |= ui
+                    .checkbox(&mut settings.graphics.shadows, loc.get("settings.shadows"))
                     .changed();
 
                 changed |= ui
-                    .checkbox(&mut settings.graphics.shadows, "Shadows")
+                    .checkbox(&mut settings.graphics.starfield, loc.get("settings.background"))
                     .changed();
 
                 changed |= ui
-                    .checkbox(&mut settings.graphics.starfield, "Background")
+                    .checkbox(&mut settings.graphics.screen_shake, loc.get("settings.screen_shake"))
                     .changed();
             });
 
+            ui.group(|ui| {
+                ui.strong(loc.get("settings.controls"));
+                changed |= controls(ui, &mut settings.controls, &mut rebinding, &loc);
+            });
+
             changed |= ui
-                .checkbox(&mut settings.debug.developer_mode, "Developer mode")
+                .checkbox(&mut settings.debug.developer_mode, loc.get("settings.developer_mode"))
                 .changed();
 
+            ui.horizontal(|ui| {
+                if ui
+                    .button(loc.get("settings.diagnostics"))
+                    .on_hover_text(loc.get("settings.diagnostics_hint"))
+                    .clicked()
+                {
+                    let report = diagnostics_report();
+                    ui.output_mut(|output| output.copied_text = report.clone());
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        *diagnostics_status = match write_report("diagnostics", &report) {
+                            Some(path) => {
+                                loc.format("settings.diagnostics_saved", &[("path", &path)])
+                            }
+                            None => loc.get("settings.diagnostics_copied"),
+                        };
+                    }
+                    #[cfg(target_arch = "wasm32")]
+                    {
+                        info!("diagnostics:\n{report}");
+                        *diagnostics_status = loc.get("settings.diagnostics_printed");
+                    }
+                }
+                ui.label(diagnostics_status.as_str());
+            });
+
             if changed {
                 settings_res.set_changed();
             }
         });
 
-        if ui.button("Back to menu").clicked() {
-            next_state.set(MenuState::MainMenu);
+        if ui.button(loc.get("menu.back_to_menu")).clicked() {
+            close_menu.send_default();
+        }
+    });
+}
+
+/// Same as controls section of the settings menu, but available directly from pause menu
+fn draw_controls_menu(
+    mut egui_ctx: EguiContexts,
+    mut close_menu: EventWriter<CloseMenu>,
+    mut settings_res: ResMut<AppSettings>,
+    mut rebinding: ResMut<Rebinding>,
+    loc: Res<Localization>,
+) {
+    EguiPopup {
+        name: "draw_controls_menu",
+        ..default()
+    }
+    .show(egui_ctx.ctx_mut(), |ui| {
+        ui.heading(loc.get("settings.controls"));
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            let settings = settings_res.bypass_change_detection();
+            if controls(ui, &mut settings.controls, &mut rebinding, &loc) {
+                settings_res.set_changed();
+            }
+        });
+
+        ui.label("");
+        if ui.button(loc.get("menu.back_to_menu")).clicked() {
+            close_menu.send_default();
         }
     });
 }
+
+fn collision_matrix(ui: &mut egui::Ui) {
+    egui::Grid::new("collision_matrix")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            for ty in PhysicsType::all() {
+                ui.small(ty.name());
+            }
+            ui.end_row();
+
+            for ty in PhysicsType::all() {
+                ui.small(ty.name());
+                for other in PhysicsType::all() {
+                    match (ty.collides(other), ty.collides_with().contains(&other)) {
+                        (true, _) => ui.label("X"),
+                        (false, true) => ui
+                            .colored_label(Color::ORANGE_RED.to_egui(), "!")
+                            .on_hover_text("Specified only for one of the layers"),
+                        (false, false) => ui.label(""),
+                    };
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Returns true if bindings were changed
+fn controls(
+    ui: &mut egui::Ui,
+    controls: &mut ControlSettings,
+    rebinding: &mut Rebinding,
+    loc: &Localization,
+) -> bool {
+    let mut all_bindings = vec![];
+    all_bindings.extend(bindings(ControlsMap::App, &controls.app));
+    all_bindings.extend(bindings(ControlsMap::Player, &controls.player));
+    all_bindings.extend(bindings(ControlsMap::Editor, &controls.editor));
+
+    let mut changed = false;
+
+    ui.collapsing(loc.get("controls.game"), |ui| {
+        changed |= controls_map(
+            ui,
+            ControlsMap::Player,
+            &mut controls.player,
+            &all_bindings,
+            rebinding,
+            loc,
+        );
+    });
+    ui.collapsing(loc.get("controls.menu"), |ui| {
+        changed |= controls_map(
+            ui,
+            ControlsMap::App,
+            &mut controls.app,
+            &all_bindings,
+            rebinding,
+            loc,
+        );
+    });
+    ui.collapsing(loc.get("controls.editor"), |ui| {
+        changed |= controls_map(
+            ui,
+            ControlsMap::Editor,
+            &mut controls.editor,
+            &all_bindings,
+            rebinding,
+            loc,
+        );
+    });
+
+    if ui.button(loc.get("controls.reset")).clicked() {
+        *controls = default();
+        rebinding.0 = None;
+        changed = true;
+    }
+
+    changed
+}
+
+/// Map, action name, input
+type Binding = (ControlsMap, String, UserInput);
+
+fn bindings<A: ActionInfo>(map_kind: ControlsMap, map: &InputMap<A>) -> Vec<Binding> {
+    map.iter()
+        .flat_map(|(inputs, action)| {
+            inputs
+                .iter()
+                .map(move |input| (map_kind, format!("{action:?}"), input.clone()))
+        })
+        .collect()
+}
+
+/// Returns true if bindings were changed
+fn controls_map<A: ActionInfo>(
+    ui: &mut egui::Ui,
+    map_kind: ControlsMap,
+    map: &mut InputMap<A>,
+    all_bindings: &[Binding],
+    rebinding: &mut Rebinding,
+    loc: &Localization,
+) -> bool {
+    let mut changed = false;
+
+    egui::Grid::new(format!("controls_map_{map_kind:?}"))
+        .striped(true)
+        .show(ui, |ui| {
+            for action in A::variants() {
+                let name = format!("{action:?}");
+                ui.label(&name);
+
+                ui.horizontal(|ui| {
+                    let mut remove = None;
+
+                    for (index, input) in map.get(action).iter().enumerate() {
+                        let conflicts: Vec<_> = all_bindings
+                            .iter()
+                            .filter(|(other_kind, other_name, other_input)| {
+                                map_kind.active_with(*other_kind)
+                                    && other_input == input
+                                    && !(*other_kind == map_kind && *other_name == name)
+                            })
+                            .map(|(_, other_name, _)| other_name.as_str())
+                            .collect();
+
+                        let text = input_name(input, loc);
+                        match conflicts.is_empty() {
+                            true => ui.label(text),
+                            false => ui
+                                .colored_label(Color::ORANGE_RED.to_egui(), text)
+                                .on_hover_text(loc.format(
+                                    "controls.conflict",
+                                    &[("actions", &conflicts.join(", "))],
+                                )),
+                        };
+                        let remove_button = ui.small_button("x");
+                        if remove_button.on_hover_text(loc.get("controls.remove")).clicked() {
+                            remove = Some(index);
+                        }
+                    }
+
+                    if let Some(index) = remove {
+                        map.remove_at(action, index);
+                        changed = true;
+                    }
+
+                    let is_target = rebinding.0.as_ref().is_some_and(|target| {
+                        target.map == map_kind && target.action == action.index()
+                    });
+                    if is_target {
+                        let text = match rebinding.0.as_ref().and_then(|t| t.dpad.as_ref()) {
+                            Some(keys) => {
+                                let direction = ["up", "left", "down", "right"][keys.len()];
+                                loc.get(&format!("controls.press_{direction}"))
+                            }
+                            None => loc.get("controls.press_any"),
+                        };
+                        ui.colored_label(Color::YELLOW.to_egui(), text);
+                        if ui.small_button(loc.get("controls.cancel")).clicked() {
+                            rebinding.0 = None;
+                        }
+                    } else if ui
+                        .small_button("+")
+                        .on_hover_text(loc.get("controls.add"))
+                        .clicked()
+                    {
+                        rebinding.0 = Some(RebindTarget {
+                            map: map_kind,
+                            action: action.index(),
+                            dpad: action.is_dpad().then(Vec::new),
+                        });
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+    changed
+}
+
+fn cancel_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
+
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad: Res<Input<GamepadButton>>,
+    mut app_actions: ResMut<ActionState<AppActions>>,
+    mut settings: ResMut<AppSettings>,
+    mut egui_ctx: EguiContexts,
+) {
+    let Some(target) = rebinding.0.as_mut() else { return; };
+
+    // escape cancels rebinding instead of closing the menu
+    if keys.just_pressed(KeyCode::Escape) {
+        app_actions.consume(AppActions::CloseMenu);
+        rebinding.0 = None;
+        return;
+    }
+
+    let input: UserInput = match &mut target.dpad {
+        Some(dpad) => {
+            let Some(key) = keys.get_just_pressed().next() else { return; };
+            dpad.push(*key);
+
+            let &[up, left, down, right] = dpad.as_slice() else { return; };
+            VirtualDPad {
+                up: InputKind::Keyboard(up),
+                down: InputKind::Keyboard(down),
+                left: InputKind::Keyboard(left),
+                right: InputKind::Keyboard(right),
+            }
+            .into()
+        }
+        None => {
+            if let Some(key) = keys.get_just_pressed().next() {
+                (*key).into()
+            } else if let Some(button) = mouse.get_just_pressed().next() {
+                // clicks on the menu itself (i.e. "cancel" button) are not bindings
+                let ctx = egui_ctx.ctx_mut();
+                if ctx.is_pointer_over_area() || ctx.wants_pointer_input() {
+                    return;
+                }
+                (*button).into()
+            } else if let Some(button) = gamepad.get_just_pressed().next() {
+                button.button_type.into()
+            } else {
+                return;
+            }
+        }
+    };
+
+    let controls = &mut settings.controls;
+    match target.map {
+        ControlsMap::App => insert_binding(&mut controls.app, target.action, input),
+        ControlsMap::Player => insert_binding(&mut controls.player, target.action, input),
+        ControlsMap::Editor => insert_binding(&mut controls.editor, target.action, input),
+    }
+    rebinding.0 = None;
+}
+
+fn insert_binding<A: Actionlike>(map: &mut InputMap<A>, action: usize, input: UserInput) {
+    if let Some(action) = A::get_at(action) {
+        map.insert(input, action);
+    }
+}
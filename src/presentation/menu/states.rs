@@ -19,9 +19,14 @@ pub enum MenuState {
     None,
 
     MainMenu,
+    /// Game is running, but paused
+    Pause,
     LevelSelect,
     Settings,
+    Controls,
+    Upgrades,
     LevelEditor,
+    MessageHistory,
 
     ModalMessage,
     Help,
@@ -43,6 +48,7 @@ impl Plugin for StatesPlugin {
                 Last,
                 update_game_controls.run_if(state_changed::<MenuState>()),
             )
+            .add_systems(OnEnter(GameRunning::No), on_game_exit)
             .add_systems(
                 PostUpdate,
                 (
@@ -68,6 +74,16 @@ fn update_game_controls(
     };
 }
 
+/// Game can be exited from the pause menu or from console
+fn on_game_exit(state: Res<State<MenuState>>, mut next_state: ResMut<NextState<MenuState>>) {
+    match state.get() {
+        MenuState::None | MenuState::Pause | MenuState::Help => {
+            next_state.set(MenuState::MainMenu)
+        }
+        _ => (),
+    }
+}
+
 fn on_load_complete(
     mut next_state: ResMut<NextState<MenuState>>,
     settings: Res<AppSettings>,
@@ -88,7 +104,11 @@ fn on_load_complete(
             MenuState::None
         });
 
-        game_commands.send(GameCommand::Start { level_id });
+        // editor doesn't use checkpoints
+        game_commands.send(match settings.debug.quick_edit {
+            true => GameCommand::Start { level_id },
+            false => GameCommand::Continue { level_id },
+        });
     } else {
         next_state.set(MenuState::MainMenu);
     }
@@ -101,16 +121,26 @@ fn on_actions(
     mut next_state: ResMut<NextState<MenuState>>,
     game_running: Res<State<GameRunning>>,
 ) {
+    // menus which can be opened both from main and pause menus
+    let parent_menu = match game_running.get() {
+        GameRunning::Yes => MenuState::Pause,
+        GameRunning::No => MenuState::MainMenu,
+    };
+
     if actions.just_pressed(AppActions::CloseMenu) || close_menu.read().count() != 0 {
         match state.get() {
             MenuState::Startup => (),
-            MenuState::None => next_state.set(MenuState::MainMenu),
+            MenuState::None => next_state.set(MenuState::Pause),
             MenuState::MainMenu => match game_running.get() {
                 GameRunning::Yes => next_state.set(MenuState::None),
                 GameRunning::No => (),
             },
+            MenuState::Pause => next_state.set(MenuState::None),
             MenuState::LevelSelect => next_state.set(MenuState::MainMenu),
-            MenuState::Settings => next_state.set(MenuState::MainMenu),
+            MenuState::Settings => next_state.set(parent_menu),
+            MenuState::Controls => next_state.set(parent_menu),
+            MenuState::Upgrades => next_state.set(parent_menu),
+            MenuState::MessageHistory => next_state.set(MenuState::Pause),
             MenuState::LevelEditor => (),
             MenuState::ModalMessage => match game_running.get() {
                 GameRunning::Yes => next_state.set(MenuState::None),
@@ -0,0 +1,68 @@
+use super::states::CloseMenu;
+use super::states::MenuState;
+use crate::app::localization::Localization;
+use crate::app::scores::Scores;
+use crate::gameplay::mechanics::abilities::Ability;
+use crate::utils::bevy_egui::*;
+use bevy::prelude::*;
+
+pub struct UpgradesPlugin;
+
+impl Plugin for UpgradesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            draw_upgrades_menu.run_if(in_state(MenuState::Upgrades)),
+        );
+    }
+}
+
+fn draw_upgrades_menu(
+    mut egui_ctx: EguiContexts,
+    mut close_menu: EventWriter<CloseMenu>,
+    mut scores: ResMut<Scores>,
+    loc: Res<Localization>,
+) {
+    EguiPopup {
+        name: "draw_upgrades_menu",
+        ..default()
+    }
+    .show(egui_ctx.ctx_mut(), |ui| {
+        let upgrades = &scores.upgrades;
+        let mut upgrade = None;
+
+        ui.heading(loc.get("upgrades.title"));
+        ui.label(loc.format("upgrades.points", &[("points", &upgrades.points)]));
+        ui.small(loc.get("upgrades.hint"));
+        ui.label("");
+
+        egui::Grid::new("draw_upgrades_menu").show(ui, |ui| {
+            for ability in Ability::all() {
+                let level = upgrades.level(ability);
+
+                ui.label(ability.name(&loc));
+                ui.label(format!("{level}/{}", Ability::MAX_LEVEL));
+
+                if level < Ability::MAX_LEVEL {
+                    let text = ability.upgrade_description(level + 1, &loc);
+                    let button = egui::Button::new(text);
+                    if ui.add_enabled(upgrades.can_upgrade(ability), button).clicked() {
+                        upgrade = Some(ability);
+                    }
+                } else {
+                    ui.label(loc.get("upgrades.max_level"));
+                }
+                ui.end_row();
+            }
+        });
+
+        if let Some(ability) = upgrade {
+            scores.upgrades.upgrade(ability);
+        }
+
+        ui.label("");
+        if ui.button(loc.get("menu.back_to_menu")).clicked() {
+            close_menu.send_default();
+        }
+    });
+}
@@ -5,7 +5,8 @@ mod menu;
 mod objects;
 
 pub use advanced_gizmos::AdvancedGizmos;
-pub use menu::{DelayedMessage, Message, MessageType};
+pub use menu::{DelayedMessage, Message, MessageCategory, MessageType};
+pub use objects::CameraPan;
 
 pub struct PresentationPlugin;
 
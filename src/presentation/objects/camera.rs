@@ -0,0 +1,366 @@
+use super::WorldCameraBundle;
+use crate::app::actions::PlayerActions;
+use crate::app::scheduling::SpawnSet;
+use crate::app::settings::AppSettings;
+use crate::gameplay::master::level::current::CurrentLevel;
+use crate::gameplay::master::level::current::LevelCommand;
+use crate::gameplay::master::level::data::TILE_SIZE;
+use crate::gameplay::master::script_points::ScriptPoint;
+use crate::gameplay::master::time_master::TimeMaster;
+use crate::gameplay::objects::barrels::Explosion;
+use crate::gameplay::objects::player::Player;
+use crate::utils::bevy::misc_utils::ImmediateTransformUpdate;
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use leafwing_input_manager::action_state::ActionState;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Multipliers for [`WorldCameraBundle::VERTICAL_SIZE`], switched by [`PlayerActions::Zoom`]
+const ZOOM_LEVELS: [f32; 3] = [1., 1.4, 0.75];
+
+/// How fast camera catches up with the target, per second
+const FOLLOW_RATE: f32 = 6.;
+const ZOOM_RATE: f32 = 4.;
+
+/// Camera is shifted towards aim point by this fraction of distance to it
+const LOOK_AHEAD: f32 = 0.25;
+const LOOK_AHEAD_MAX: f32 = 3.;
+
+/// How far outside of level extents camera can see
+const BOUNDS_MARGIN: f32 = TILE_SIZE;
+
+/// At maximum trauma
+const SHAKE_OFFSET: f32 = 0.6;
+/// At maximum trauma, in radians
+const SHAKE_ANGLE: f32 = 0.05;
+/// Per second
+const TRAUMA_DECAY: f32 = 1.2;
+/// At the center of explosion, decreases linearly to zero at the radius
+const EXPLOSION_TRAUMA: f32 = 0.6;
+const EXPLOSION_TRAUMA_RADIUS: f32 = 12.;
+
+/// Time to move from player to the point or back
+const PAN_TRAVEL: Duration = Duration::from_millis(800);
+/// Game is slowed down to full stop during camera pan
+const PAN_TIME_RAMP: Duration = Duration::from_millis(200);
+const PAN_TIME_MODIFIER: &str = "camera_pan";
+
+/// Move camera to the [`ScriptPoint`] and back. Game is paused meanwhile.
+///
+/// If there are several points with the same ID, camera moves to their center.
+/// Pans are queued and cancelled when level is changed.
+#[derive(Event, Clone)]
+pub struct CameraPan {
+    /// Script point ID
+    pub point: String,
+    /// How long camera stays at the point
+    pub hold: Duration,
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CameraPan>()
+            .init_resource::<CameraZoom>()
+            .init_resource::<CameraBounds>()
+            .init_resource::<CameraPans>()
+            .add_systems(
+                Update,
+                (
+                    switch_zoom,
+                    add_explosion_trauma.run_if(on_event::<Explosion>()),
+                    update_bounds.run_if(resource_changed::<CurrentLevel>()),
+                    (
+                        cancel_pans.run_if(on_event::<LevelCommand>()),
+                        queue_pans.run_if(on_event::<CameraPan>()),
+                    )
+                        .chain(),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    spawn_camera.in_set(SpawnSet::Controllers),
+                    (camera_follow, update_pans, camera_tracking)
+                        .chain()
+                        .after(TransformSystem::TransformPropagate),
+                ),
+            );
+    }
+}
+
+/// Camera which follows player
+#[derive(Component)]
+struct PlayerCamera {
+    /// Smoothed position of followed point
+    pos: Option<Vec2>,
+    /// Smoothed zoom level
+    zoom: f32,
+    /// Screen shake strength, from 0 to 1
+    trauma: f32,
+    /// Advances only while shaking
+    shake_time: f32,
+}
+
+/// Index in [`ZOOM_LEVELS`]
+#[derive(Resource, Default)]
+struct CameraZoom(usize);
+
+/// Level extents
+#[derive(Resource, Default)]
+struct CameraBounds(Option<Rect>);
+
+#[derive(Resource, Default)]
+struct CameraPans {
+    queue: VecDeque<CameraPan>,
+    active: Option<ActivePan>,
+}
+
+struct ActivePan {
+    target: Vec2,
+    hold: Duration,
+    elapsed: Duration,
+}
+
+impl ActivePan {
+    fn duration(&self) -> Duration {
+        PAN_TRAVEL * 2 + self.hold
+    }
+
+    /// From 0 (player) to 1 (target)
+    fn progress(&self) -> f32 {
+        let travel = PAN_TRAVEL.as_secs_f32();
+        let t = self.elapsed.as_secs_f32();
+        let back = (self.duration() - PAN_TRAVEL).as_secs_f32();
+
+        let t = match t {
+            t if t < travel => t / travel,
+            t if t < back => 1.,
+            t => 1. - (t - back) / travel,
+        };
+        let t = t.clamp(0., 1.);
+        t * t * (3. - 2. * t) // smoothstep
+    }
+}
+
+fn spawn_camera(new: Query<(), Added<Player>>, mut commands: Commands, zoom: Res<CameraZoom>) {
+    for _ in new.iter() {
+        commands.spawn((
+            WorldCameraBundle::new("player camera", false),
+            PlayerCamera {
+                pos: None,
+                zoom: ZOOM_LEVELS[zoom.0],
+                trauma: 0.,
+                shake_time: 0.,
+            },
+        ));
+    }
+}
+
+fn switch_zoom(
+    actions: Res<ActionState<PlayerActions>>,
+    time_master: Res<TimeMaster>,
+    mut zoom: ResMut<CameraZoom>,
+) {
+    if actions.just_pressed(PlayerActions::Zoom) && !time_master.in_menu {
+        zoom.0 = (zoom.0 + 1) % ZOOM_LEVELS.len();
+    }
+}
+
+fn add_explosion_trauma(
+    mut explosions: EventReader<Explosion>,
+    mut cameras: Query<(&GlobalTransform, &mut PlayerCamera)>,
+) {
+    for explosion in explosions.read() {
+        for (transform, mut camera) in cameras.iter_mut() {
+            let distance = transform.translation().truncate().distance(explosion.at.truncate());
+            let falloff = (1. - distance / EXPLOSION_TRAUMA_RADIUS).max(0.);
+            camera.trauma = (camera.trauma + EXPLOSION_TRAUMA * falloff).min(1.);
+        }
+    }
+}
+
+fn update_bounds(level: Res<CurrentLevel>, mut bounds: ResMut<CameraBounds>) {
+    bounds.0 = level.data.extents().map(|rect| Rect {
+        min: rect.min - BOUNDS_MARGIN,
+        max: rect.max + BOUNDS_MARGIN,
+    });
+}
+
+fn cancel_pans(
+    mut level_commands: EventReader<LevelCommand>,
+    mut pans: ResMut<CameraPans>,
+    mut time_master: ResMut<TimeMaster>,
+) {
+    let level_changed = level_commands
+        .read()
+        .any(|command| matches!(command, LevelCommand::Load(_) | LevelCommand::Unload));
+
+    if level_changed {
+        pans.queue.clear();
+        if pans.active.take().is_some() {
+            time_master.remove(PAN_TIME_MODIFIER);
+        }
+    }
+}
+
+fn queue_pans(mut new_pans: EventReader<CameraPan>, mut pans: ResMut<CameraPans>) {
+    pans.queue.extend(new_pans.read().cloned());
+}
+
+/// Real time is used, so camera isn't affected by slow-motion
+fn camera_delta(time_master: &TimeMaster, time: &Time<Real>) -> Duration {
+    match time_master.in_menu || time_master.in_editor {
+        true => Duration::ZERO,
+        false => time.delta(),
+    }
+}
+
+/// Follow player, zoom and decay screen shake
+fn camera_follow(
+    mut camera: Query<&mut PlayerCamera>,
+    player: Query<(&GlobalTransform, &Player)>,
+    zoom: Res<CameraZoom>,
+    settings: Res<AppSettings>,
+    time_master: Res<TimeMaster>,
+    time: Res<Time<Real>>,
+) {
+    let Ok(mut camera) = camera.get_single_mut() else { return; };
+    let Ok((player_pos, player)) = player.get_single() else { return; };
+
+    let dt = camera_delta(&time_master, &time).as_secs_f32();
+
+    let player_pos = player_pos.translation().truncate();
+    let look_ahead = player
+        .input_aim
+        .map(|aim| ((aim - player_pos) * LOOK_AHEAD).clamp_length_max(LOOK_AHEAD_MAX))
+        .unwrap_or_default();
+    let target = player_pos + look_ahead;
+
+    let pos = match camera.pos {
+        Some(pos) => pos.lerp(target, 1. - (-FOLLOW_RATE * dt).exp()),
+        None => target,
+    };
+    camera.pos = Some(pos);
+
+    let target_zoom = ZOOM_LEVELS[zoom.0];
+    camera.zoom += (target_zoom - camera.zoom) * (1. - (-ZOOM_RATE * dt).exp());
+
+    camera.trauma = (camera.trauma - TRAUMA_DECAY * dt).max(0.);
+    if settings.graphics.screen_shake && camera.trauma > 0. {
+        camera.shake_time += dt;
+    }
+}
+
+/// Start queued pans and advance the active one
+fn update_pans(
+    camera: Query<(), With<PlayerCamera>>,
+    points: Query<(&GlobalTransform, &ScriptPoint)>,
+    mut pans: ResMut<CameraPans>,
+    mut time_master: ResMut<TimeMaster>,
+    time: Res<Time<Real>>,
+) {
+    if camera.is_empty() {
+        return;
+    }
+
+    if pans.active.is_none() {
+        if let Some(pan) = pans.queue.pop_front() {
+            let (sum, count) = points
+                .iter()
+                .filter(|(_, point)| point.id == pan.point)
+                .fold((Vec2::ZERO, 0), |(sum, count), (pos, _)| {
+                    (sum + pos.translation().truncate(), count + 1)
+                });
+
+            match count {
+                0 => warn!("camera pan: no script point \"{}\"", pan.point),
+                _ => {
+                    pans.active = Some(ActivePan {
+                        target: sum / count as f32,
+                        hold: pan.hold,
+                        elapsed: Duration::ZERO,
+                    });
+                    time_master.push(PAN_TIME_MODIFIER, 0., PAN_TIME_RAMP);
+                }
+            }
+        }
+    }
+
+    let delta = camera_delta(&time_master, &time);
+    if let Some(pan) = pans.active.as_mut() {
+        pan.elapsed += delta;
+
+        if pan.elapsed >= pan.duration() {
+            pans.active = None;
+            time_master.remove(PAN_TIME_MODIFIER);
+        }
+    }
+}
+
+fn camera_tracking(
+    camera: Query<(Entity, &PlayerCamera, &Projection)>,
+    mut transform: ImmediateTransformUpdate<(Without<Player>, Without<ScriptPoint>)>,
+    bounds: Res<CameraBounds>,
+    pans: Res<CameraPans>,
+    settings: Res<AppSettings>,
+) {
+    let Ok((entity, camera, projection)) = camera.get_single() else { return; };
+    let Some(pos) = camera.pos else { return; };
+
+    // scripted pan
+
+    let view_pos = match &pans.active {
+        Some(pan) => pos.lerp(pan.target, pan.progress()),
+        None => pos,
+    };
+
+    // keep level in view
+
+    let vertical_size = WorldCameraBundle::VERTICAL_SIZE * camera.zoom;
+    let aspect_ratio = match projection {
+        Projection::Perspective(projection) => projection.aspect_ratio,
+        Projection::Orthographic(_) => 1.,
+    };
+    let half_size = Vec2::new(vertical_size * aspect_ratio, vertical_size) / 2.;
+
+    let view_pos = match bounds.0 {
+        Some(bounds) => Vec2::new(
+            clamp_to_bounds(view_pos.x, half_size.x, bounds.min.x, bounds.max.x),
+            clamp_to_bounds(view_pos.y, half_size.y, bounds.min.y, bounds.max.y),
+        ),
+        None => view_pos,
+    };
+
+    // screen shake
+
+    let shake = match settings.graphics.screen_shake {
+        true => camera.trauma * camera.trauma,
+        false => 0.,
+    };
+
+    let t = camera.shake_time;
+    let offset = Vec2::new(shake_noise(t, 0.), shake_noise(t, 10.)) * SHAKE_OFFSET * shake;
+    let angle = shake_noise(t, 20.) * SHAKE_ANGLE * shake;
+
+    transform.update_inplace(entity, |transform| {
+        transform.translation =
+            (view_pos + offset).extend(WorldCameraBundle::distance(vertical_size));
+        transform.rotation = Quat::from_rotation_z(angle);
+    });
+}
+
+/// Center if level is smaller than the view
+fn clamp_to_bounds(value: f32, half_size: f32, min: f32, max: f32) -> f32 {
+    match max - min <= half_size * 2. {
+        true => (min + max) / 2.,
+        false => value.clamp(min + half_size, max - half_size),
+    }
+}
+
+/// Smooth pseudo-random value in -1..1 range
+fn shake_noise(t: f32, seed: f32) -> f32 {
+    ((t * 23. + seed).sin() + (t * 37. + seed * 1.7).sin() * 0.5) / 1.5
+}
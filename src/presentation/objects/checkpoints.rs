@@ -0,0 +1,65 @@
+use super::assets::ObjectAssets;
+use crate::app::scheduling::SpawnSet;
+use crate::gameplay::master::checkpoints::Checkpoint;
+use crate::gameplay::master::checkpoints::CheckpointReached;
+use crate::utils::bevy::commands::FallibleCommands;
+use bevy::prelude::*;
+
+pub struct CheckpointsPlugin;
+
+impl Plugin for CheckpointsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (spawn_checkpoints.in_set(SpawnSet::Controllers), on_reached),
+        );
+    }
+}
+
+#[derive(Component)]
+struct CheckpointMaterial(Handle<StandardMaterial>);
+
+fn checkpoint_color(reached: bool) -> Color {
+    match reached {
+        true => Color::LIME_GREEN,
+        false => Color::CYAN.with_a(0.5),
+    }
+}
+
+fn spawn_checkpoints(
+    new: Query<Entity, Added<Checkpoint>>,
+    mut commands: Commands,
+    assets: Res<ObjectAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for entity in new.iter() {
+        let material = materials.add(StandardMaterial {
+            base_color: checkpoint_color(false),
+            unlit: true,
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        });
+        let pbr = PbrBundle {
+            mesh: assets.mesh_cube.clone(),
+            material: material.clone(),
+            transform: Transform::from_scale(Vec3::new(1.6, 1.6, 0.05)),
+            ..default()
+        };
+
+        commands.try_insert(entity, CheckpointMaterial(material));
+        commands.try_with_children(entity, |parent| {
+            parent.spawn(pbr);
+        });
+    }
+}
+
+fn on_reached(
+    checkpoints: Query<&CheckpointMaterial, Added<CheckpointReached>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for material in checkpoints.iter() {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color = checkpoint_color(true);
+        }
+    }
+}
@@ -2,6 +2,7 @@ use super::assets::ObjectAssets;
 use super::utils::rotate_3to2_tr;
 use crate::app::scheduling::SpawnSet;
 use crate::gameplay::objects::conveyor::Conveyor;
+use crate::gameplay::objects::conveyor::ConveyorSwitch;
 use crate::utils::bevy::commands::FallibleCommands;
 use bevy::prelude::*;
 
@@ -9,7 +10,20 @@ pub struct ConveyorPlugin;
 
 impl Plugin for ConveyorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostUpdate, (spawn_conv.in_set(SpawnSet::Controllers),));
+        app.add_systems(
+            PostUpdate,
+            (spawn_conv.in_set(SpawnSet::Controllers), update_switches),
+        );
+    }
+}
+
+#[derive(Component)]
+struct SwitchMaterial(Handle<StandardMaterial>);
+
+fn switch_color(active: bool) -> Color {
+    match active {
+        true => Color::LIME_GREEN,
+        false => Color::ORANGE_RED,
     }
 }
 
@@ -17,10 +31,11 @@ fn spawn_conv(
     new: Query<(Entity, &Conveyor), Added<Conveyor>>,
     mut commands: Commands,
     assets: Res<ObjectAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     for (entity, object) in new.iter() {
         match object {
-            Conveyor::Belt => {
+            Conveyor::Belt { .. } => {
                 let scene = assets.scene_belt.clone();
 
                 commands.try_with_children(entity, |parent| {
@@ -43,6 +58,36 @@ fn spawn_conv(
                     });
                 });
             }
+
+            Conveyor::Switch { .. } => {
+                let material = materials.add(StandardMaterial {
+                    base_color: switch_color(false),
+                    unlit: true,
+                    ..default()
+                });
+                let pbr = PbrBundle {
+                    mesh: assets.mesh_cube.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_scale(Vec3::new(1.2, 1.2, 0.1)),
+                    ..default()
+                };
+
+                commands.try_insert(entity, SwitchMaterial(material));
+                commands.try_with_children(entity, |parent| {
+                    parent.spawn(pbr);
+                });
+            }
+        }
+    }
+}
+
+fn update_switches(
+    switches: Query<(&ConveyorSwitch, &SwitchMaterial), Changed<ConveyorSwitch>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (switch, material) in switches.iter() {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color = switch_color(switch.active);
         }
     }
 }
@@ -3,6 +3,8 @@ use bevy::prelude::*;
 mod animation_ctl;
 mod assets;
 mod barrels;
+mod camera;
+mod checkpoints;
 mod conveyor;
 mod elevators;
 mod enemy;
@@ -14,6 +16,8 @@ mod starfield;
 mod terrain;
 mod utils;
 
+pub use camera::CameraPan;
+pub use starfield::Star;
 pub use utils::WorldCameraBundle;
 
 pub struct ObjectsPlugin;
@@ -34,6 +38,7 @@ impl Plugin for ObjectsPlugin {
             materials::MaterialsPlugin,
             starfield::StarfieldPlugin,
             conveyor::ConveyorPlugin,
+            (checkpoints::CheckpointsPlugin, camera::CameraPlugin),
         ));
     }
 }
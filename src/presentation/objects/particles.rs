@@ -2,6 +2,7 @@ use super::assets::ObjectAssets;
 use super::materials::Materials;
 use super::utils::rotate_3to2;
 use crate::app::scheduling::SpawnSet;
+use crate::gameplay::balance::Balance;
 use crate::gameplay::master::level::spawn::GameObjectBundle;
 use crate::gameplay::mechanics::damage::DamageType;
 use crate::gameplay::mechanics::damage::Dead;
@@ -64,9 +65,10 @@ fn spawn_particles(
     mut commands: Commands,
     assets: Res<ObjectAssets>,
     materials: Res<Materials>,
+    balance: Res<Balance>,
 ) {
     for (entity, object) in new.iter() {
-        let descr = object.descriptor();
+        let descr = balance.particle(*object);
         let scale = Vec3::splat(descr.graphical_size);
 
         let (material, shadows) = match object {
@@ -3,7 +3,6 @@ use super::animation_ctl::AnimationCtlSystem;
 use super::assets::ModelAsset;
 use super::assets::ObjectAssets;
 use super::utils::rotate_3to2_tr;
-use super::WorldCameraBundle;
 use crate::app::scheduling::SpawnSet;
 use crate::gameplay::mechanics::damage::Dead;
 use crate::gameplay::mechanics::damage::Health;
@@ -11,11 +10,9 @@ use crate::gameplay::objects::player::Player;
 use crate::gameplay::objects::player::PlayerState;
 use crate::utils::bevy::commands::ExtendedEntityMut;
 use crate::utils::bevy::commands::FallibleCommands;
-use crate::utils::bevy::misc_utils::ImmediateTransformUpdate;
 use crate::utils::misc_utils::ExtendedTime;
 use crate::utils::random::RandomBool as _;
 use bevy::prelude::*;
-use bevy::transform::TransformSystem;
 use std::time::Duration;
 
 pub struct PlayerPlugin;
@@ -26,17 +23,12 @@ impl Plugin for PlayerPlugin {
             PostUpdate,
             (
                 spawn_player.in_set(SpawnSet::Controllers),
-                camera_tracking.after(TransformSystem::TransformPropagate),
                 update_player_animation.before(AnimationCtlSystem),
             ),
         );
     }
 }
 
-/// Marker for camera which follows player
-#[derive(Component)]
-struct PlayerCamera;
-
 /// Player graphical state
 #[derive(Component)]
 struct PlayerData {
@@ -52,6 +44,7 @@ enum PlayerAnimation {
     LookAround,
     LookBack,
     Walking,
+    Dash,
     Hit,
     Kick,
     Dead,
@@ -72,6 +65,8 @@ impl PlayerAnimation {
             Self::LookAround => ("look_around", false, 3.),
             Self::LookBack => ("look_back", false, 3.),
             Self::Walking => ("walk", true, 0.8),
+            // model has no dash animation, so it's a fast walk cycle
+            Self::Dash => ("walk", false, 0.2),
             Self::Hit => ("hit", false, 0.4),
             Self::Kick => ("kick", false, 0.7),
             Self::Dead => ("dead", true, 1.),
@@ -86,6 +81,7 @@ impl PlayerAnimation {
             Self::LookAround,
             Self::LookBack,
             Self::Walking,
+            Self::Dash,
             Self::Hit,
             Self::Kick,
             Self::Dead,
@@ -131,25 +127,9 @@ fn spawn_player(
                 had_health: default(),
             });
         });
-
-        commands.spawn((WorldCameraBundle::new("player camera", false), PlayerCamera));
     }
 }
 
-fn camera_tracking(
-    camera: Query<Entity, With<PlayerCamera>>,
-    player: Query<&GlobalTransform, With<Player>>,
-    mut transform: ImmediateTransformUpdate<Without<Player>>,
-) {
-    let Some((camera, player)) = camera.get_single().ok().zip(player.get_single().ok()) else { return; };
-
-    transform.update_inplace(camera, |transform| {
-        let z = transform.translation.z;
-        transform.translation = player.translation();
-        transform.translation.z = z;
-    });
-}
-
 fn update_player_animation(
     mut player: Query<(&mut Player, &mut PlayerData, &Health)>,
     mut animations: Query<&mut AnimationCtl>,
@@ -175,6 +155,10 @@ fn update_player_animation(
             animation.set_active(PlayerAnimation::Death, true);
         }
 
+        if std::mem::take(&mut player.dash_animation) {
+            animation.set_active(PlayerAnimation::Dash, true);
+        }
+
         if std::mem::take(&mut player.kick_animation) {
             animation.set_active(PlayerAnimation::Kick, true);
         }
@@ -18,8 +18,9 @@ impl Plugin for StarfieldPlugin {
     }
 }
 
+/// Background star, spawned and moved by the starfield
 #[derive(Component)]
-struct Star;
+pub struct Star;
 
 fn update_stars(
     mut stars: Query<(Entity, &mut Transform), With<Star>>,
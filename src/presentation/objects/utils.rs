@@ -30,11 +30,16 @@ pub struct WorldCameraBundle {
 
 impl WorldCameraBundle {
     pub const VERTICAL_SIZE: f32 = 12.; // viewport height in world units
+    const VERTICAL_FOV: f32 = 0.5026548; // 28.8 degrees, 70 mm
+
+    /// Distance from the ground plane at which viewport height is `vertical_size`
+    pub fn distance(vertical_size: f32) -> f32 {
+        (vertical_size / 2.) / (Self::VERTICAL_FOV / 2.).tan()
+    }
 
     pub fn new(name: impl Into<std::borrow::Cow<'static, str>>, is_editor: bool) -> Self {
-        let vertical_fov = 28.8_f32.to_radians(); // 70 mm
-        let vertical_size = Self::VERTICAL_SIZE;
-        let distance = (vertical_size / 2.) / (vertical_fov / 2.).tan();
+        let vertical_fov = Self::VERTICAL_FOV;
+        let distance = Self::distance(Self::VERTICAL_SIZE);
 
         Self {
             name: Name::new(name),
@@ -52,6 +52,27 @@ pub fn serde_sorted_map<S: serde::Serializer, K: Serialize + Ord, V: Serialize>(
     BTreeMap::from_iter(items).serialize(serializer)
 }
 
+/// Serialize [`Duration`] as seconds, which is much more readable than default representation.
+///
+/// Use attribute `#[serde(with = "serde_duration_secs")]`
+pub mod serde_duration_secs {
+    use serde::de::Error;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        value.as_secs_f32().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = f32::deserialize(deserializer)?;
+        Duration::try_from_secs_f32(secs).map_err(D::Error::custom)
+    }
+}
+
 /// Weird helper methods for [`EventReader`]
 pub trait ExtendedEventReader<E> {
     /// Panics if there is more than one event
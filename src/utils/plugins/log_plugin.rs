@@ -2,9 +2,12 @@ use bevy::log::Level;
 use bevy::prelude::*;
 use bevy::utils::tracing::field;
 use bevy::utils::tracing::Subscriber;
+use std::collections::VecDeque;
 use std::fmt::Write as _;
+use std::sync::Mutex;
 use tracing_log::LogTracer;
 use tracing_subscriber::layer::SubscriberExt as _;
+use tracing_subscriber::reload;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::Layer;
 use tracing_subscriber::Registry;
@@ -12,9 +15,53 @@ use tracing_subscriber::Registry;
 #[derive(Event, Clone)]
 pub struct LogMessage {
     pub level: Level,
+    /// Usually module path
+    pub target: String,
     pub text: String,
 }
 
+/// Filter for log messages which can be changed at runtime
+#[derive(Resource)]
+pub struct LogFilter {
+    base: String,
+    extra: String,
+    handle: reload::Handle<EnvFilter, Registry>,
+}
+
+impl LogFilter {
+    /// Filter set by [`LogPlugin`] (or `RUST_LOG` environment variable), in [`EnvFilter`] format
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// Directives added to the base filter
+    pub fn extra(&self) -> &str {
+        &self.extra
+    }
+
+    /// Check if directives are valid, without applying them
+    pub fn check(&self, extra: &str) -> Result<(), String> {
+        self.make_filter(extra).map(|_| ())
+    }
+
+    /// Replace added directives. On error previous filter is kept.
+    pub fn set_extra(&mut self, extra: &str) -> Result<(), String> {
+        let filter = self.make_filter(extra)?;
+        self.handle.reload(filter).map_err(|e| e.to_string())?;
+        self.extra = extra.trim().to_string();
+        Ok(())
+    }
+
+    fn make_filter(&self, extra: &str) -> Result<EnvFilter, String> {
+        let extra = extra.trim();
+        let filter = match extra.is_empty() {
+            true => self.base.clone(),
+            false => format!("{},{extra}", self.base),
+        };
+        EnvFilter::try_new(filter).map_err(|e| e.to_string())
+    }
+}
+
 /// Butchered version of [`bevy::log::LogPlugin`], which can output messages as bevy events
 pub struct LogPlugin {
     /// Filters logs using the [`EnvFilter`] format
@@ -50,12 +97,30 @@ impl LogPlugin {
 
 const EVENT_CHANNEL_CAPACITY: usize = 1024;
 
+/// How many last messages are kept for [`recent_log_lines`]
+const RECENT_LINES_COUNT: usize = 200;
+
+/// Last log messages, oldest first
+static RECENT_LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Last log messages, oldest first. Can be called from panic hook.
+pub fn recent_log_lines() -> Vec<String> {
+    // don't block if panic happened while logging
+    match RECENT_LINES.try_lock() {
+        Ok(lines) => lines.iter().cloned().collect(),
+        Err(_) => vec!["<log is not available>".to_string()],
+    }
+}
+
 impl Plugin for LogPlugin {
     fn build(&self, app: &mut App) {
         let default_filter = { format!("{},{}", self.level, self.filter) };
-        let filter_layer = EnvFilter::try_from_default_env()
-            .or_else(|_| EnvFilter::try_new(&default_filter))
-            .unwrap();
+        let base_filter = std::env::var(EnvFilter::DEFAULT_ENV)
+            .ok()
+            .filter(|filter| EnvFilter::try_new(filter).is_ok())
+            .unwrap_or(default_filter);
+        let filter_layer = EnvFilter::try_new(&base_filter).unwrap();
+        let (filter_layer, filter_handle) = reload::Layer::new(filter_layer);
         let subscriber = Registry::default().with(filter_layer);
 
         let fmt_layer = tracing_subscriber::fmt::Layer::default()
@@ -66,6 +131,14 @@ impl Plugin for LogPlugin {
         let fmt_layer = fmt_layer.with_writer(std::io::stderr);
         let subscriber = subscriber.with(fmt_layer);
 
+        #[cfg(not(target_arch = "wasm32"))]
+        let subscriber = subscriber.with(
+            tracing_subscriber::fmt::Layer::default()
+                .map_event_format(|f| f.with_line_number(true))
+                .with_ansi(false)
+                .with_writer(Mutex::new(LogFile::open())),
+        );
+
         #[cfg(target_arch = "wasm32")]
         console_error_panic_hook::set_once();
         #[cfg(target_arch = "wasm32")]
@@ -76,6 +149,11 @@ impl Plugin for LogPlugin {
         let (sender, receiver) = crossbeam_channel::bounded(EVENT_CHANNEL_CAPACITY);
         app.add_event::<LogMessage>()
             .insert_resource(LogReceiver(receiver))
+            .insert_resource(LogFilter {
+                base: base_filter,
+                extra: default(),
+                handle: filter_handle,
+            })
             .add_systems(Update, transceive_events);
 
         let events_layer = SendLayer { sender };
@@ -110,8 +188,16 @@ impl<S: Subscriber> Layer<S> for SendLayer {
 
         event.record(&mut FieldVisitor(&mut text));
 
+        if let Ok(mut lines) = RECENT_LINES.lock() {
+            if lines.len() >= RECENT_LINES_COUNT {
+                lines.pop_front();
+            }
+            lines.push_back(format!("{} {text}", event.metadata().level()));
+        }
+
         let _ = self.sender.send(LogMessage {
             level: *event.metadata().level(),
+            target: event.metadata().target().to_string(),
             text,
         });
     }
@@ -128,3 +214,77 @@ impl field::Visit for FieldVisitor<'_> {
         }
     }
 }
+
+/// Log file in userdata directory. Rotated on startup and when it gets too big.
+#[cfg(not(target_arch = "wasm32"))]
+struct LogFile {
+    file: Option<std::fs::File>,
+    size: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LogFile {
+    /// Rotate when current file gets bigger than this
+    const MAX_SIZE: u64 = 4 * 1024 * 1024;
+
+    /// How many old files are kept
+    const KEEP_OLD: usize = 3;
+
+    fn path(index: usize) -> String {
+        use super::userdata_plugin::USER_DIRECTORY;
+        match index {
+            0 => format!("{USER_DIRECTORY}/log.txt"),
+            index => format!("{USER_DIRECTORY}/log.{index}.txt"),
+        }
+    }
+
+    fn open() -> Self {
+        let mut log = Self {
+            file: None,
+            size: 0,
+        };
+        log.rotate();
+        log
+    }
+
+    /// Logging is not initialized yet (or is being done), so errors are printed directly
+    fn rotate(&mut self) {
+        self.file = None;
+
+        let _ = std::fs::create_dir_all(super::userdata_plugin::USER_DIRECTORY);
+        let _ = std::fs::remove_file(Self::path(Self::KEEP_OLD));
+        for index in (0..Self::KEEP_OLD).rev() {
+            let _ = std::fs::rename(Self::path(index), Self::path(index + 1));
+        }
+
+        self.size = 0;
+        self.file = match std::fs::File::create(Self::path(0)) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("failed to create log file: {e}");
+                None
+            }
+        };
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::io::Write for LogFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.size > Self::MAX_SIZE {
+            self.rotate();
+        }
+
+        let Some(file) = &mut self.file else { return Ok(buf.len()); };
+        let written = file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
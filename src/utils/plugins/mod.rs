@@ -2,6 +2,7 @@ use bevy::prelude::*;
 
 pub mod load_assets;
 pub mod log_plugin;
+pub mod profiler;
 pub mod userdata_plugin;
 
 /// All utility plugins except for log plugin - as it must be added before any other plugins,
@@ -13,6 +14,7 @@ impl Plugin for UtilPlugin {
         app.add_plugins((
             userdata_plugin::UserdataPlugin,
             load_assets::LoadAssetsPlugin,
+            profiler::ProfilerPlugin,
         ));
     }
 }
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use bevy::utils::Instant;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// For how many frames timings are kept
+pub const PROFILER_HISTORY: usize = 240;
+
+/// Checked by [`ProfileScope`], so it costs nothing while profiler is off
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Total time and number of calls of each scope during current frame
+static CURRENT_FRAME: Mutex<BTreeMap<&'static str, (Duration, u32)>> = Mutex::new(BTreeMap::new());
+
+/// Measures time until dropped and adds it to [`Profiler`] under that name.
+///
+/// Usage: `let _scope = ProfileScope::new("update_overload");`
+pub struct ProfileScope {
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl ProfileScope {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            start: ENABLED.load(Ordering::Relaxed).then(Instant::now),
+        }
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        let Some(start) = self.start else { return; };
+        let elapsed = start.elapsed();
+
+        if let Ok(mut frame) = CURRENT_FRAME.lock() {
+            let (time, calls) = frame.entry(self.name).or_default();
+            *time += elapsed;
+            *calls += 1;
+        }
+    }
+}
+
+/// Per-frame history of a single scope
+#[derive(Default)]
+pub struct ScopeTimings {
+    /// Total time per frame, newest last
+    pub frames: VecDeque<Duration>,
+    /// Number of calls during last frame
+    pub calls: u32,
+}
+
+impl ScopeTimings {
+    pub fn last(&self) -> Duration {
+        self.frames.back().copied().unwrap_or_default()
+    }
+
+    pub fn average(&self) -> Duration {
+        let total: Duration = self.frames.iter().sum();
+        total / self.frames.len().max(1) as u32
+    }
+
+    pub fn max(&self) -> Duration {
+        self.frames.iter().max().copied().unwrap_or_default()
+    }
+}
+
+/// Frame times and timings of [`ProfileScope`]s for last [`PROFILER_HISTORY`] frames
+#[derive(Resource, Default)]
+pub struct Profiler {
+    /// Newest last
+    pub frame_times: VecDeque<Duration>,
+    pub scopes: BTreeMap<&'static str, ScopeTimings>,
+}
+
+impl Profiler {
+    pub fn is_enabled(&self) -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    /// Disabling clears all collected data
+    pub fn set_enabled(&mut self, enabled: bool) {
+        ENABLED.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.frame_times.clear();
+            self.scopes.clear();
+            if let Ok(mut frame) = CURRENT_FRAME.lock() {
+                frame.clear();
+            }
+        }
+    }
+}
+
+/// Collects timings of [`ProfileScope`]s
+pub struct ProfilerPlugin;
+
+impl Plugin for ProfilerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Profiler>()
+            .add_systems(Last, collect_timings);
+    }
+}
+
+fn push_limited(frames: &mut VecDeque<Duration>, value: Duration) {
+    if frames.len() >= PROFILER_HISTORY {
+        frames.pop_front();
+    }
+    frames.push_back(value);
+}
+
+fn collect_timings(mut profiler: ResMut<Profiler>, time: Res<Time<Real>>) {
+    if !profiler.is_enabled() {
+        return;
+    }
+
+    let frame = match CURRENT_FRAME.lock() {
+        Ok(mut frame) => std::mem::take(&mut *frame),
+        Err(_) => return,
+    };
+
+    push_limited(&mut profiler.frame_times, time.delta());
+
+    // scopes which weren't called this frame still get a zero entry, so histories stay aligned
+    for name in frame.keys() {
+        profiler.scopes.entry(name).or_default();
+    }
+    for (name, timings) in profiler.scopes.iter_mut() {
+        let (time, calls) = frame.get(name).copied().unwrap_or_default();
+        push_limited(&mut timings.frames, time);
+        timings.calls = calls;
+    }
+}
@@ -3,7 +3,7 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::time::Duration;
 
-const USER_DIRECTORY: &str = "user"; // not for web; in current directory, i.e. project root
+pub const USER_DIRECTORY: &str = "user"; // not for web; in current directory, i.e. project root
 const COOKIE_EXPIRATION_DAYS: u64 = 7; // for web; how long before cookies are removed by the browser
 
 const SECONDS_IN_DAY: u64 = 60 * 60 * 24;